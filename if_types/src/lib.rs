@@ -12,6 +12,7 @@ pub struct NewOrderRequest {
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct NewOrderRequestItem {
     pub item_id: i32,
+    pub supply_warehouse_id: i32,
     pub quantity: i32,
 }
 
@@ -28,6 +29,21 @@ pub struct NewOrderContents {
     pub total_amount: f64,
 }
 
+/// New-Order Transaction rolled back by unused item number,
+/// TPC-C standard spec. 2.4.2.3
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct NewOrderRollbackResponse {
+    pub contents: NewOrderRollbackContents,
+    pub perf: PerformanceMetrics,
+}
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct NewOrderRollbackContents {
+    pub warehouse_id: i32,
+    pub district_id: i32,
+    pub customer_id: i32,
+    pub message: String,
+}
+
 /// Payment Transaction input,
 /// TPC-C standard spec. 2.5
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -120,6 +136,8 @@ pub struct DeliveryRequest {
     pub carrier_id: i32,
 }
 
+/// Acknowledgement of queued Delivery Transaction,
+/// TPC-C standard spec. 2.7.2
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct DeliveryResponse {
     pub contents: DeliveryContents,
@@ -127,7 +145,44 @@ pub struct DeliveryResponse {
 }
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct DeliveryContents {
-    pub deliverd_orders: i32,
+    pub delivery_id: u64,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Result of deferred Delivery Transaction,
+/// TPC-C standard spec. 2.7.4
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct DeliveryStatusResponse {
+    pub delivery_id: u64,
+    pub warehouse_id: i32,
+    pub carrier_id: i32,
+    pub status: DeliveryStatus,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds_option")]
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Time waiting in the queue
+    pub queue_secs: Option<f64>,
+    /// Execution time of the database transaction
+    pub perf: Option<PerformanceMetrics>,
+    pub districts: Vec<DeliveryDistrict>,
+    pub error: Option<String>,
+}
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct DeliveryDistrict {
+    pub district_id: i32,
+    pub order_ids: Vec<i32>,
+    /// No new order to deliver in this district
+    pub skipped: bool,
 }
 
 /// Stock-Level Transaction input,
@@ -151,10 +206,64 @@ pub struct StockLevelContents {
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct PrepareDbRequest {
     pub scale_factor: i32,
+    /// Loader threads, default and maximum are database connections of SUT
+    #[serde(default)]
+    pub workers: Option<u32>,
+    /// Seed of random numbers for reproducible population, random if None
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// c_since, h_date and o_entry_d of loaded rows, now if None (fixed timestamp with seed)
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub load_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-/// Performance metrics
+/// Acknowledgement of started preparation job
 #[derive(serde::Deserialize, serde::Serialize)]
+pub struct PrepareDbResponse {
+    pub job_id: u64,
+}
+
+/// Progress of preparation job
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct PrepareDbStatusResponse {
+    pub job_id: u64,
+    pub scale_factor: i32,
+    pub seed: Option<u64>,
+    pub phase: PreparePhase,
+    /// Warehouses whose all rows are loaded
+    pub warehouses_done: i64,
+    /// Rows loaded in all tables
+    pub rows: i64,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds_option")]
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub error: Option<String>,
+    /// Database status after completion
+    pub db_status: Option<DbStatusResponse>,
+}
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PreparePhase {
+    Queued,
+    /// Drop and create tables
+    Schema,
+    /// Load initial population
+    Load,
+    /// Vacuum and analyze
+    Vacuum,
+    Completed,
+    Failed,
+}
+
+impl PreparePhase {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// Performance metrics
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct PerformanceMetrics {
     /// Time to take BEGIN TRANSACTION
     pub begin: f64,
@@ -162,32 +271,269 @@ pub struct PerformanceMetrics {
     pub query: f64,
     /// Commit transaction
     pub commit: f64,
+    /// Retries by contention before successful attempt
+    #[serde(default)]
+    pub retries: u32,
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+/// Query of database status
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct DbStatusParams {
+    /// Include rows and sizes of each table, which scans all tables
+    #[serde(default)]
+    pub tables: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct DbStatusResponse {
     pub warehouse_count: i64,
     pub district_count: i64,
     pub customer_count: i64,
     pub order_count: i64,
     pub database_bytes: i64,
+    /// Isolation level of transactions in SUT
+    #[serde(default)]
+    pub isolation_level: String,
+    /// Read transactions run as READ ONLY
+    #[serde(default)]
+    pub read_only: bool,
+    /// Database settings in effect, e.g. SQLite PRAGMAs
+    #[serde(default)]
+    pub database_settings: std::collections::BTreeMap<String, String>,
+    /// Rows and sizes of each table, empty unless requested
+    #[serde(default)]
+    pub tables: Vec<TableStatus>,
+    /// C_LOAD of NURand used in initial population, None if not prepared
+    #[serde(default)]
+    pub nurand_constants: Option<NURandConstants>,
     pub statistics: Statistics,
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+/// Constant C of NURand(A, x, y) for each field, TPC-C standard spec. 2.1.6
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
+pub struct NURandConstants {
+    /// C for C_LAST, A = 255
+    pub c_last: i32,
+    /// C for C_ID, A = 1023
+    pub c_id: i32,
+    /// C for OL_I_ID, A = 8191
+    pub ol_i_id: i32,
+}
+
+/// Rows and sizes of table
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct TableStatus {
+    pub name: String,
+    pub rows: i64,
+    /// Table size in bytes, including indexes
+    pub bytes: i64,
+    /// Size of indexes in bytes
+    pub index_bytes: i64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct Statistics {
     pub new_order_count: i64,
     pub new_order_secs: f64,
+    pub new_order_rollback_count: i64,
+    pub new_order_rollback_secs: f64,
     pub payment_count: i64,
     pub payment_secs: f64,
     pub order_status_count: i64,
     pub order_status_secs: f64,
     pub delivery_count: i64,
     pub delivery_secs: f64,
+    pub delivery_queue_secs: f64,
     pub stock_level_count: i64,
     pub stock_level_secs: f64,
     pub customer_by_id_count: i64,
     pub customer_by_id_secs: f64,
     pub customer_by_name_count: i64,
     pub customer_by_name_secs: f64,
+    /// Transactions retried by contention
+    pub retry_count: i64,
+    /// Transactions failed after max attempts
+    pub retry_exhausted_count: i64,
+}
+
+/// Benchmark result reported by RTE
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct BenchmarkReport {
+    pub params: BenchmarkParams,
+    /// New-Order transactions per minute in measurement interval
+    pub tpmc: f64,
+    /// New-Order transactions in measurement interval
+    pub new_order_count: i64,
+    /// Counts and response times for each request type, in measurement interval
+    pub transactions: Vec<TransactionReport>,
+    /// Counts and response times for each request type, in ramp-up and ramp-down
+    pub ramp_transactions: Vec<TransactionReport>,
+    /// 90th percentile response time constraints, TPC-C standard spec. 5.2.5.3
+    pub response_time_checks: Vec<ResponseTimeCheck>,
+    /// Transaction mix, TPC-C standard spec. 5.2.3
+    pub mix: Vec<TransactionShare>,
+    /// Violations of TPC-C standard found in the run
+    pub warnings: Vec<String>,
+    /// SUT database status after the run
+    pub db_status: DbStatusResponse,
+}
+
+/// Parameters of benchmark run
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct BenchmarkParams {
+    pub endpoint: String,
+    /// Warehouse count in SUT database
+    pub scale_factor: i32,
+    /// Terminals per warehouse
+    pub concurrent: i32,
+    pub terminals: i32,
+    /// Measurement interval in secs
+    pub duration: f64,
+    pub ramp_up: f64,
+    pub ramp_down: f64,
+    pub wait: f64,
+    pub payment_weight: u32,
+    pub order_status_weight: u32,
+    pub delivery_weight: u32,
+    pub stock_level_weight: u32,
+    /// Seed of random numbers for reproducible requests, random if None
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// C_RUN of NURand, TPC-C standard spec. 2.1.6.1
+    #[serde(default)]
+    pub nurand_constants: Option<NURandConstants>,
+}
+
+/// Counts and response times of single request type
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TransactionReport {
+    pub name: String,
+    pub counts: u64,
+    /// Failed requests, sum of failures below
+    pub errors: u64,
+    /// 404 from SUT, or no customer found by last name
+    #[serde(default)]
+    pub not_found: u64,
+    /// Other error status from SUT
+    #[serde(default)]
+    pub server_errors: u64,
+    /// No response within request timeout
+    #[serde(default)]
+    pub timeouts: u64,
+    /// Failed to connect, send request or read response
+    #[serde(default)]
+    pub transport_errors: u64,
+    /// Retries by contention in SUT
+    pub retries: u64,
+    /// End to end response time measured in RTE
+    pub e2e: LatencyReport,
+    /// Time to take BEGIN TRANSACTION in SUT
+    pub begin: LatencyReport,
+    /// Query time in SUT
+    pub query: LatencyReport,
+    /// Commit time in SUT
+    pub commit: LatencyReport,
+}
+
+/// Latency distribution in secs
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct LatencyReport {
+    pub avg: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ResponseTimeCheck {
+    pub transaction: String,
+    /// 90th percentile of end to end response time in secs
+    pub p90: f64,
+    /// Limit in secs
+    pub limit: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TransactionShare {
+    pub transaction: String,
+    /// Share in percent
+    pub share: f64,
+    /// Minimum share in percent
+    pub minimum: f64,
+}
+
+/// Throughput and latency of single request type in single interval
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TimeSeriesSample {
+    /// Start of interval, in secs from start of run
+    pub time: f64,
+    /// ramp_up, measurement or ramp_down
+    pub phase: String,
+    pub transaction: String,
+    pub counts: u64,
+    pub errors: u64,
+    /// Counts per minute
+    pub per_minute: f64,
+    /// Average of end to end response time in secs
+    pub e2e_avg: f64,
+    /// Maximum of end to end response time in secs
+    pub e2e_max: f64,
+}
+
+/// Result of consistency check
+/// TPC-C standard spec. 3.3.2
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ConsistencyResponse {
+    /// All conditions are satisfied, except for initial_only conditions
+    pub consistent: bool,
+    pub conditions: Vec<ConsistencyCondition>,
+    pub perf: PerformanceMetrics,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ConsistencyCondition {
+    /// Condition number, TPC-C standard spec. 3.3.2.x
+    pub number: u32,
+    pub description: String,
+    /// Only required to be maintained in initial database population
+    pub initial_only: bool,
+    pub checked: i64,
+    pub violations: i64,
+    /// Violating rows
+    pub samples: Vec<String>,
+}
+
+/// Result of validation of initial population
+/// TPC-C standard spec. 4.3.3.1
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct PopulationResponse {
+    /// All rules are satisfied and all shares are in tolerance
+    pub valid: bool,
+    pub rules: Vec<PopulationRule>,
+    pub shares: Vec<PopulationShare>,
+    pub perf: PerformanceMetrics,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct PopulationRule {
+    pub description: String,
+    pub checked: i64,
+    pub violations: i64,
+    /// Violating rows
+    pub samples: Vec<String>,
+}
+
+/// Share of rows having random property
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct PopulationShare {
+    pub description: String,
+    pub rows: i64,
+    pub matched: i64,
+    /// Share in percent
+    pub share: f64,
+    /// Expected share in percent
+    pub expected: f64,
+    pub valid: bool,
 }
@@ -14,6 +14,10 @@ enum Command {
     Prepare(PrepareArgs),
     /// Run benchmark
     Run(RunArgs),
+    /// Check consistency conditions of database
+    Check(CheckArgs),
+    /// Validate initial population of database
+    Validate(CheckArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -21,25 +25,177 @@ struct PrepareArgs {
     /// Scale factor (Warehouse count for TPC-C)
     #[arg(short, long, default_value = "1")]
     scale_factor: i32,
+    /// Loader threads in SUT, 1 with --seed [default: database connections of SUT]
+    #[arg(short, long)]
+    workers: Option<u32>,
+    /// Timeout to wait for completion of preparation in secs
+    #[arg(long, default_value = "3600")]
+    timeout: u64,
+    /// Seed of random numbers to load the same database by the same seed [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Timestamp of initial rows in RFC 3339, e.g. 2025-01-01T00:00:00Z
+    /// [default: now, 2025-01-01T00:00:00Z with --seed]
+    #[arg(long)]
+    load_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[command(flatten)]
+    client: ClientArgs,
+    /// Endpoint URL of SUT
+    endpoint: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    #[command(flatten)]
+    client: ClientArgs,
     /// Endpoint URL of SUT
     endpoint: String,
 }
 
 #[derive(clap::Args, Debug)]
 struct RunArgs {
-    /// Cuncurrency / scale_factor
+    /// Concurrent terminals per warehouse
     #[arg(short, long, default_value = "1")]
     concurrent: i32,
-    /// Duration in secs
+    /// Duration of measurement interval in secs
     #[arg(short, long, default_value = "60")]
     duration: f32,
+    /// Ramp-up time before measurement interval in secs
+    #[arg(long, default_value = "5", value_parser = non_negative_secs)]
+    ramp_up: f32,
+    /// Ramp-down time after measurement interval in secs
+    #[arg(long, default_value = "5", value_parser = non_negative_secs)]
+    ramp_down: f32,
     /// Wait : 0.0 for no wait, 1.0 for value in TPC-C spec.
-    #[arg(short, long, default_value = "1.0")]
+    #[arg(short, long, default_value = "1.0", value_parser = non_negative_secs)]
     wait: f32,
+    /// Payment share in transaction mix (%), New-Order takes the rest
+    #[arg(long, default_value = "43", value_parser = clap::value_parser!(u32).range(..=100))]
+    payment_weight: u32,
+    /// Order-Status share in transaction mix (%)
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u32).range(..=100))]
+    order_status_weight: u32,
+    /// Delivery share in transaction mix (%)
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u32).range(..=100))]
+    delivery_weight: u32,
+    /// Stock-Level share in transaction mix (%)
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u32).range(..=100))]
+    stock_level_weight: u32,
+    /// Write benchmark report to file
+    #[arg(short, long)]
+    output: Option<std::path::PathBuf>,
+    /// Format of report and time series files
+    #[arg(long, value_enum, default_value = "json")]
+    format: ReportFormat,
+    /// Write throughput and latency of each interval to file
+    #[arg(long)]
+    time_series: Option<std::path::PathBuf>,
+    /// Sampling interval of time series in secs
+    #[arg(long, default_value = "10", value_parser = positive_secs)]
+    interval: f32,
+    /// Print progress line for each interval
+    #[arg(long)]
+    progress: bool,
+    /// Seed of random numbers to send the same requests by the same seed [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Abort run when failed transactions exceed this share of all transactions (%)
+    /// [default: never abort]
+    #[arg(long)]
+    max_error_rate: Option<f64>,
+    #[command(flatten)]
+    client: ClientArgs,
+    /// Each terminal has own HTTP client and connections, instead of sharing one client
+    #[arg(long)]
+    client_per_terminal: bool,
     /// Endpoint URL of SUT
     endpoint: String,
 }
 
+/// Options of HTTP client to SUT
+#[derive(clap::Args, Debug)]
+struct ClientArgs {
+    /// Timeout of each request in secs, timed out transaction is counted as error
+    /// [default: 5 for run, 60 for prepare, 600 for check and validate]
+    #[arg(long, value_parser = positive_secs)]
+    request_timeout: Option<f32>,
+    /// Timeout to connect to SUT in secs [default: within request timeout]
+    #[arg(long, value_parser = positive_secs)]
+    connect_timeout: Option<f32>,
+    /// HTTP version, http2 is HTTP/2 over cleartext with prior knowledge
+    #[arg(long, value_enum, default_value = "http1")]
+    http: HttpVersion,
+    /// Maximum idle connections kept in pool [default: unlimited]
+    #[arg(long)]
+    pool_size: Option<usize>,
+    /// Keep idle connections for reuse in secs, 0 to close connection after each request
+    #[arg(long, default_value = "90", value_parser = non_negative_secs)]
+    keep_alive: f32,
+}
+
+/// Parse secs of timeout, positive and within range of Duration
+fn positive_secs(arg: &str) -> Result<f32, String> {
+    let secs = non_negative_secs(arg)?;
+    if secs == 0.0 {
+        return Err("must be positive".to_string());
+    }
+    Ok(secs)
+}
+
+/// Parse secs, zero or positive and within range of Duration
+fn non_negative_secs(arg: &str) -> Result<f32, String> {
+    let secs = arg.parse::<f32>().map_err(|e| e.to_string())?;
+    std::time::Duration::try_from_secs_f32(secs)
+        .map_err(|_| "must be finite and not negative secs".to_string())?;
+    Ok(secs)
+}
+
+impl ClientArgs {
+    /// Build HTTP client, `default_timeout` in secs unless --request-timeout
+    fn build(&self, default_timeout: f32) -> reqwest::Result<reqwest::Client> {
+        let timeout = self.request_timeout.unwrap_or(default_timeout);
+        self.builder()
+            .timeout(std::time::Duration::from_secs_f32(timeout))
+            .build()
+    }
+
+    /// Client builder without request timeout
+    fn builder(&self) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder();
+        let builder = match self.connect_timeout {
+            Some(timeout) => builder.connect_timeout(std::time::Duration::from_secs_f32(timeout)),
+            None => builder,
+        };
+        let builder = match self.http {
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+        let builder = if 0.0 < self.keep_alive {
+            builder.pool_idle_timeout(std::time::Duration::from_secs_f32(self.keep_alive))
+        } else {
+            builder.pool_max_idle_per_host(0)
+        };
+        match self.pool_size {
+            Some(size) if 0.0 < self.keep_alive => builder.pool_max_idle_per_host(size),
+            _ => builder,
+        }
+    }
+}
+
+/// HTTP version of requests to SUT
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum HttpVersion {
+    Http1,
+    Http2,
+}
+
+/// Format of benchmark report file
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum ReportFormat {
+    Json,
+    Csv,
+}
+
 /// Endpoint URLs for each request
 struct EndpointUrls {
     base: url::Url,
@@ -48,6 +204,8 @@ struct EndpointUrls {
     customer: url::Url,
     delivery: url::Url,
     prepare_db: url::Url,
+    consistency: url::Url,
+    population: url::Url,
 }
 
 impl TryFrom<&str> for EndpointUrls {
@@ -61,6 +219,8 @@ impl TryFrom<&str> for EndpointUrls {
             customer: base.join("/customers")?,
             delivery: base.join("/delivery")?,
             prepare_db: base.join("/prepare_db")?,
+            consistency: base.join("/consistency")?,
+            population: base.join("/population")?,
             base,
         })
     }
@@ -73,6 +233,11 @@ impl EndpointUrls {
     pub fn prepare_db(&self) -> url::Url {
         self.prepare_db.clone()
     }
+    pub fn prepare_db_status(&self, job_id: u64) -> url::Url {
+        let mut prepare_db_status = self.base.clone();
+        prepare_db_status.set_path(&format!("/prepare_db/{}", job_id));
+        prepare_db_status
+    }
     pub fn payment(&self) -> url::Url {
         self.payment.clone()
     }
@@ -97,11 +262,15 @@ impl EndpointUrls {
     pub fn customer_by_lastname(&self) -> url::Url {
         self.customer.clone()
     }
-    /*
     pub fn status(&self) -> url::Url {
         self.base.clone()
     }
-    */
+    pub fn consistency(&self) -> url::Url {
+        self.consistency.clone()
+    }
+    pub fn population(&self) -> url::Url {
+        self.population.clone()
+    }
 }
 
 #[tokio::main]
@@ -113,35 +282,95 @@ async fn main() -> Result<(), Error> {
     match cli.command {
         Command::Prepare(args) => prepare(args).await?,
         Command::Run(args) => run(args).await?,
+        Command::Check(args) => check(args).await?,
+        Command::Validate(args) => validate(args).await?,
     }
 
     Ok(())
 }
 
 /// Prepare database
+///
+/// SUT runs preparation as a background job, poll its progress until completion.
 async fn prepare(args: PrepareArgs) -> Result<(), Error> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(60))
-        .build()?;
+    let client = args.client.build(60.0)?;
 
     let endpoints = EndpointUrls::try_from(args.endpoint.as_str())?;
     let endpoint = endpoints.prepare_db();
     log::info!(
-        "Requesting POST {} with scale_factor={}",
+        "Requesting POST {} with scale_factor={}, seed={:?}",
         endpoint.as_str(),
-        args.scale_factor
+        args.scale_factor,
+        args.seed
     );
 
     let t = std::time::Instant::now();
-    let resp = client
+    let job = client
         .post(endpoint)
         .json(&if_types::PrepareDbRequest {
             scale_factor: args.scale_factor,
+            workers: args.workers,
+            seed: args.seed,
+            load_time: args.load_time,
         })
         .send()
+        .await?
+        .error_for_status()?
+        .json::<if_types::PrepareDbResponse>()
         .await?;
+    log::info!("Preparation job {} started", job.job_id);
+
+    let timeout = std::time::Duration::from_secs(args.timeout);
+    let mut last_progress = None;
+    let resp = loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let status = client
+            .get(endpoints.prepare_db_status(job.job_id))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<if_types::PrepareDbStatusResponse>()
+            .await?;
+
+        match status.phase {
+            if_types::PreparePhase::Completed => {
+                break status
+                    .db_status
+                    .ok_or("Preparation completed without database status")?;
+            }
+            if_types::PreparePhase::Failed => {
+                return Err(format!(
+                    "Preparation job {} failed : {}",
+                    job.job_id,
+                    status.error.unwrap_or_default()
+                )
+                .into());
+            }
+            phase => {
+                let progress = (phase, status.warehouses_done, status.rows);
+                if last_progress != Some(progress) {
+                    log::info!(
+                        "{:?} : {} of {} warehouses, {} rows in {:.0}s",
+                        phase,
+                        status.warehouses_done,
+                        status.scale_factor,
+                        status.rows,
+                        t.elapsed().as_secs_f32()
+                    );
+                    last_progress = Some(progress);
+                }
+            }
+        }
+
+        if timeout < t.elapsed() {
+            return Err(format!(
+                "Preparation job {} did not complete in {}s",
+                job.job_id, args.timeout
+            )
+            .into());
+        }
+    };
 
-    let resp = resp.json::<if_types::DbStatusResponse>().await?;
     log::info!(
         "Preparation succeeded in {:.03}s",
         t.elapsed().as_secs_f32()
@@ -151,182 +380,1209 @@ async fn prepare(args: PrepareArgs) -> Result<(), Error> {
     println!("  customer  = {}", resp.customer_count);
     println!("  order     = {}", resp.order_count);
     println!("  db bytes  = {}", resp.database_bytes);
+    println!(
+        "  isolation = {}{}",
+        resp.isolation_level,
+        if resp.read_only { ", read only" } else { "" }
+    );
+    for (name, value) in &resp.database_settings {
+        println!("  {:12} = {}", name, value);
+    }
+    print_tables(&resp.tables);
 
     Ok(())
 }
 
+/// Print rows and sizes of tables in SUT database
+fn print_tables(tables: &[if_types::TableStatus]) {
+    if tables.is_empty() {
+        return;
+    }
+    println!("\n##            rows    ,   bytes    , index bytes");
+    for t in tables {
+        println!(
+            "{:12} {:11}, {:11}, {:11}",
+            format!("{}:", t.name),
+            t.rows,
+            t.bytes,
+            t.index_bytes
+        );
+    }
+}
+
+/// Check consistency conditions
+/// TPC-C standard spec. 3.3.2
+async fn check(args: CheckArgs) -> Result<(), Error> {
+    let client = args.client.build(600.0)?;
+
+    let endpoints = EndpointUrls::try_from(args.endpoint.as_str())?;
+    let endpoint = endpoints.consistency();
+    log::info!("Requesting GET {}", endpoint.as_str());
+
+    let t = std::time::Instant::now();
+    let resp = client
+        .get(endpoint)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<if_types::ConsistencyResponse>()
+        .await?;
+    log::info!("Checked in {:.03}s", t.elapsed().as_secs_f32());
+
+    println!("## condition,  checked , violations");
+    for condition in &resp.conditions {
+        let result = if condition.violations == 0 {
+            "ok"
+        } else if condition.initial_only {
+            "ng (initial database only)"
+        } else {
+            "ng"
+        };
+        println!(
+            "{:9}   , {:9}, {:9}  {} : {}",
+            condition.number,
+            condition.checked,
+            condition.violations,
+            result,
+            condition.description
+        );
+        for sample in &condition.samples {
+            println!("              {}", sample);
+        }
+    }
+
+    if resp.consistent {
+        log::info!("Database is consistent");
+        Ok(())
+    } else {
+        Err("Database is not consistent".into())
+    }
+}
+
+/// Validate initial population
+/// TPC-C standard spec. 4.3.3.1
+async fn validate(args: CheckArgs) -> Result<(), Error> {
+    let client = args.client.build(600.0)?;
+
+    let endpoints = EndpointUrls::try_from(args.endpoint.as_str())?;
+    let endpoint = endpoints.population();
+    log::info!("Requesting GET {}", endpoint.as_str());
+
+    let t = std::time::Instant::now();
+    let resp = client
+        .get(endpoint)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<if_types::PopulationResponse>()
+        .await?;
+    log::info!("Validated in {:.03}s", t.elapsed().as_secs_f32());
+
+    println!("##   checked , violations");
+    for rule in &resp.rules {
+        println!(
+            "{:11}, {:9}  {} : {}",
+            rule.checked,
+            rule.violations,
+            if rule.violations == 0 { "ok" } else { "ng" },
+            rule.description
+        );
+        for sample in &rule.samples {
+            println!("              {}", sample);
+        }
+    }
+
+    println!("\n##      rows ,  share   , expected");
+    for share in &resp.shares {
+        println!(
+            "{:11}, {:6.2} %, {:6.2} %  {} : {}",
+            share.rows,
+            share.share,
+            share.expected,
+            if share.valid { "ok" } else { "ng" },
+            share.description
+        );
+    }
+
+    if resp.valid {
+        log::info!("Initial population is valid");
+        Ok(())
+    } else {
+        Err("Initial population is not valid".into())
+    }
+}
+
 /// Run benchmark
 async fn run(args: RunArgs) -> Result<(), Error> {
     let endpoints = EndpointUrls::try_from(args.endpoint.as_str())?;
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
+    let client = args.client.build(5.0)?;
+    // Status of SUT database may take longer than transactions
+    let status_client = args
+        .client
+        .builder()
+        .timeout(std::time::Duration::from_secs(600))
         .build()?;
 
-    let start_t = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    if args
+        .max_error_rate
+        .is_some_and(|rate| !(0.0..=100.0).contains(&rate))
+    {
+        return Err("max-error-rate must be between 0 and 100".into());
+    }
+    let interval = std::time::Duration::from_secs_f32(args.interval);
+
+    let launch_t = std::time::Instant::now();
+    let start_t = launch_t + std::time::Duration::from_secs_f32(args.ramp_up);
     let end_t = start_t + std::time::Duration::from_secs_f32(args.duration);
-    let term_t = end_t + std::time::Duration::from_secs(5);
+    let term_t = end_t + std::time::Duration::from_secs_f32(args.ramp_down);
+    let period = BenchmarkPeriod {
+        launch_t,
+        start_t,
+        end_t,
+        term_t,
+    };
 
-    let perf: [PerfSummary; 6] = Default::default();
-    log::info!("Start benchmark");
-    let futs = (0..(args.concurrent)).map(|_i| async {
-        benchmark_single_terminal(start_t, end_t, term_t, 1, &perf, &endpoints, &client).await
+    // Cards in the deck for each transaction type
+    let mix = TransactionMix::try_from(&args)?;
+
+    // Number of warehouses in SUT database
+    let initial_status = status(&endpoints, &status_client, false).await?;
+    let warehouse_count = initial_status.warehouse_count as i32;
+    log::info!("{} warehouses in SUT", warehouse_count);
+
+    // TPC-C standard spec. 2.1.6.1
+    // C_RUN of NURand shared by all terminals, derived from C_LOAD in SUT database
+    let c_load = initial_status
+        .nurand_constants
+        .ok_or("NURand constants not found in SUT database, prepare database again")?;
+    let mut rand = match args.seed {
+        Some(seed) => tpcc_rand::TpcRandom::from_seed_stream(seed, RUN_CONSTANTS_STREAM),
+        None => tpcc_rand::TpcRandom::new(),
+    };
+    let nurand = tpcc_rand::NURandConstants {
+        c_last: c_load.c_last,
+        c_id: c_load.c_id,
+        ol_i_id: c_load.ol_i_id,
+    }
+    .for_run(&mut rand);
+    log::info!("NURand constants C_LOAD {:?}, C_RUN {:?}", c_load, nurand);
+
+    // TPC-C standard spec. 4.2.2
+    // Each warehouse has `concurrent` terminals, with fixed home district
+    let concurrent = args.concurrent;
+    let terminals = (1..=warehouse_count)
+        .flat_map(|warehouse_id| {
+            (0..concurrent).map(move |i| Terminal {
+                terminal_id: (warehouse_id - 1) * concurrent + i + 1,
+                warehouse_id,
+                district_id: i % 10 + 1,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let config = TerminalConfig {
+        warehouse_count,
+        wait: args.wait as f64,
+        mix,
+        seed: args.seed,
+        nurand,
+        max_error_rate: args.max_error_rate,
+    };
+
+    let perf: [PerfSummary; 7] = std::array::from_fn(|_| PerfSummary::new(&period, interval));
+    let abort = RunAbort::new();
+    log::info!("Start benchmark with {} terminals", terminals.len());
+    let futs = terminals.iter().map(|terminal| async {
+        let client = if args.client_per_terminal {
+            args.client.build(5.0)?
+        } else {
+            client.clone()
+        };
+        benchmark_single_terminal(
+            &period, terminal, &config, &perf, &endpoints, &client, &abort,
+        )
+        .await
     });
-    let counts = futures::future::try_join_all(futs).await?;
-    log::info!("Finished");
+    let progress = async {
+        if args.progress {
+            print_progress(&period, interval, &perf, &abort).await;
+        }
+        Ok(())
+    };
+    let (_, ()) = futures::future::try_join(futures::future::try_join_all(futs), progress).await?;
+    // Report is written even if aborted, and run fails after that
+    let aborted = abort.reason();
+    match &aborted {
+        Some(reason) => log::error!("{}", reason),
+        None => log::info!("Finished"),
+    }
 
-    let total_counts = counts.into_iter().sum::<i32>();
-    println!(
-        "\n{:.1} tpm  ( {} new_order transactions in {:.3} secs )\n",
-        (total_counts as f32) * 60.0 / args.duration,
-        total_counts,
-        args.duration,
-    );
+    // Final status of SUT database
+    let db_status = status(&endpoints, &status_client, true).await?;
 
-    println!("##                calls , e2e total,  begin   ,  query   ,  commit");
-    println!("##             ( counts ) (sec/call) (sec/call) (sec/call) (sec/call)");
-    println!(
-        "new_order:        {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[0].counts(),
-        perf[0].avg_e2e(),
-        perf[0].avg_begin(),
-        perf[0].avg_query(),
-        perf[0].avg_commit(),
-    );
-    println!(
-        "payment:          {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[1].counts(),
-        perf[1].avg_e2e(),
-        perf[1].avg_begin(),
-        perf[1].avg_query(),
-        perf[1].avg_commit(),
+    let mut report = benchmark_report(
+        &args,
+        terminals.len() as i32,
+        warehouse_count,
+        &config.nurand,
+        &perf,
+        db_status,
     );
+    if let Some(reason) = &aborted {
+        report.warnings.push(format!(
+            "{}, results of the partial run are not valid",
+            reason
+        ));
+    }
+    print_report(&report);
+
+    if let Some(output) = &args.output {
+        write_report(&report, output, args.format)?;
+        log::info!("Report written to {}", output.display());
+    }
+
+    if let Some(output) = &args.time_series {
+        let samples = time_series(&period, interval, &perf);
+        write_time_series(&samples, output, args.format)?;
+        log::info!("Time series written to {}", output.display());
+    }
+
+    match aborted {
+        Some(reason) => Err(reason.into()),
+        None => Ok(()),
+    }
+}
+
+/// Summarize benchmark results
+fn benchmark_report(
+    args: &RunArgs,
+    terminals: i32,
+    warehouse_count: i32,
+    nurand: &tpcc_rand::NURandConstants,
+    perf: &[PerfSummary; 7],
+    db_status: if_types::DbStatusResponse,
+) -> if_types::BenchmarkReport {
+    let mut warnings = vec![];
+
+    let rows = [
+        ("new_order", &perf[0]),
+        ("new_order(rb)", &perf[6]),
+        ("payment", &perf[1]),
+        ("order_status", &perf[2]),
+        ("delivery", &perf[3]),
+        ("stock_level", &perf[4]),
+        ("customer_by_name", &perf[5]),
+    ];
+    let transactions = rows
+        .iter()
+        .map(|(name, perf)| perf.measurement.to_report(name))
+        .collect();
+    let ramp_transactions = rows
+        .iter()
+        .map(|(name, perf)| perf.ramp.to_report(name))
+        .collect();
+
+    // TPC-C standard spec. 5.4.2, New-Order transactions completed in measurement interval,
+    // rolled back transactions are also counted
+    let new_orders = perf[0].counts() + perf[6].counts();
+
+    // TPC-C standard spec. 5.2.5.3, 90th percentile response time constraints
+    // rolled back New-Order transactions are also included
+    let p90_e2e = [
+        (TransactionType::NewOrder, vec![&perf[0], &perf[6]]),
+        (TransactionType::Payment, vec![&perf[1]]),
+        (TransactionType::OrderStatus, vec![&perf[2]]),
+        (TransactionType::Delivery, vec![&perf[3]]),
+        (TransactionType::StockLevel, vec![&perf[4]]),
+    ];
+    let response_time_checks = p90_e2e
+        .into_iter()
+        .map(|(transaction, summaries)| {
+            let p90 = PerfSummary::p90_e2e(&summaries);
+            let limit = transaction.response_time_limit();
+            if limit < p90 {
+                warnings.push(format!(
+                    "90th percentile response time of {} is {:.3} sec, exceeds {:.1} sec limit",
+                    transaction.name(),
+                    p90,
+                    limit
+                ));
+            }
+            if_types::ResponseTimeCheck {
+                transaction: transaction.name().to_string(),
+                p90,
+                limit,
+            }
+        })
+        .collect();
+
+    // TPC-C standard spec. 5.2.3, check minimum mix
+    let mix_counts = [
+        (
+            TransactionType::NewOrder,
+            perf[0].counts() + perf[6].counts(),
+        ),
+        (TransactionType::Payment, perf[1].counts()),
+        (TransactionType::OrderStatus, perf[2].counts()),
+        (TransactionType::Delivery, perf[3].counts()),
+        (TransactionType::StockLevel, perf[4].counts()),
+    ];
+    let total_transactions = mix_counts.iter().map(|(_, c)| c).sum::<usize>().max(1);
+    let mix = mix_counts
+        .into_iter()
+        .map(|(transaction, counts)| {
+            let share = 100.0 * counts as f64 / total_transactions as f64;
+            let minimum = transaction.min_mix();
+            if share < minimum {
+                warnings.push(format!(
+                    "{} is {:.2}% of transaction mix, less than {:.2}% required",
+                    transaction.name(),
+                    share,
+                    minimum
+                ));
+            }
+            if_types::TransactionShare {
+                transaction: transaction.name().to_string(),
+                share,
+                minimum,
+            }
+        })
+        .collect();
+
+    if_types::BenchmarkReport {
+        params: if_types::BenchmarkParams {
+            endpoint: args.endpoint.clone(),
+            scale_factor: warehouse_count,
+            concurrent: args.concurrent,
+            terminals,
+            duration: args.duration as f64,
+            ramp_up: args.ramp_up as f64,
+            ramp_down: args.ramp_down as f64,
+            wait: args.wait as f64,
+            payment_weight: args.payment_weight,
+            order_status_weight: args.order_status_weight,
+            delivery_weight: args.delivery_weight,
+            stock_level_weight: args.stock_level_weight,
+            seed: args.seed,
+            nurand_constants: Some(if_types::NURandConstants {
+                c_last: nurand.c_last,
+                c_id: nurand.c_id,
+                ol_i_id: nurand.ol_i_id,
+            }),
+        },
+        tpmc: (new_orders as f64) * 60.0 / args.duration as f64,
+        new_order_count: new_orders as i64,
+        transactions,
+        ramp_transactions,
+        response_time_checks,
+        mix,
+        warnings,
+        db_status,
+    }
+}
+
+/// Print benchmark results to stdout
+fn print_report(report: &if_types::BenchmarkReport) {
     println!(
-        "order_status:     {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[2].counts(),
-        perf[2].avg_e2e(),
-        perf[2].avg_begin(),
-        perf[2].avg_query(),
-        perf[2].avg_commit(),
+        "\n{:.1} tpm  ( {} new_order transactions in {:.3} secs )\n",
+        report.tpmc, report.new_order_count, report.params.duration,
     );
     println!(
-        "delivery:         {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[3].counts(),
-        perf[3].avg_e2e(),
-        perf[3].avg_begin(),
-        perf[3].avg_query(),
-        perf[3].avg_commit(),
+        "isolation level: {}{}\n",
+        report.db_status.isolation_level,
+        if report.db_status.read_only {
+            ", read only"
+        } else {
+            ""
+        }
     );
+    if !report.db_status.database_settings.is_empty() {
+        let settings = report
+            .db_status
+            .database_settings
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        println!("database settings: {}\n", settings.join(", "));
+    }
+
+    println!("##                calls , e2e total,  begin   ,  query   ,  commit");
+    println!("##             ( counts ) (sec/call) (sec/call) (sec/call) (sec/call)");
+    for t in &report.transactions {
+        println!(
+            "{:17} {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
+            format!("{}:", t.name),
+            t.counts,
+            t.e2e.avg,
+            t.begin.avg,
+            t.query.avg,
+            t.commit.avg,
+        );
+    }
+
+    println!("\n## ramp-up, ramp-down");
+    for t in &report.ramp_transactions {
+        println!(
+            "{:17} {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
+            format!("{}:", t.name),
+            t.counts,
+            t.e2e.avg,
+            t.begin.avg,
+            t.query.avg,
+            t.commit.avg,
+        );
+    }
+
+    println!("\n##               errors , not found,  server  ,  timeout , transport");
+    for t in &report.transactions {
+        println!(
+            "{:17} {:6}, {:9}, {:9}, {:9}, {:9}",
+            format!("{}:", t.name),
+            t.errors,
+            t.not_found,
+            t.server_errors,
+            t.timeouts,
+            t.transport_errors,
+        );
+    }
+
+    println!("\n##                       ,    p50   ,    p90   ,    p95   ,    p99   ,    max");
+    println!("##                       ,   (sec)  ,   (sec)  ,   (sec)  ,   (sec)  ,   (sec)");
+    for t in &report.transactions {
+        let latencies = [
+            ("e2e", &t.e2e),
+            ("begin", &t.begin),
+            ("query", &t.query),
+            ("commit", &t.commit),
+        ];
+        for (i, (metric, l)) in latencies.into_iter().enumerate() {
+            let label = if i == 0 {
+                format!("{}:", t.name)
+            } else {
+                String::new()
+            };
+            println!(
+                "{:17} {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
+                label, metric, l.p50, l.p90, l.p95, l.p99, l.max,
+            );
+        }
+    }
+
+    println!("\n##             90th pct , limit");
+    for check in &report.response_time_checks {
+        println!(
+            "{:17} {:9.06}, {:5.1}",
+            format!("{}:", check.transaction),
+            check.p90,
+            check.limit
+        );
+    }
+
+    // TPC-C standard spec. 2.7.2, Delivery is executed in deferred mode.
+    // Queueing and execution time are measured in SUT, since SUT started.
+    let sut_stat = &report.db_status.statistics;
+    let deliveries = sut_stat.delivery_count.max(1) as f64;
+    println!("\n## deferred in SUT  calls ,  queue   , execution");
     println!(
-        "stock_level:      {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[4].counts(),
-        perf[4].avg_e2e(),
-        perf[4].avg_begin(),
-        perf[4].avg_query(),
-        perf[4].avg_commit(),
+        "delivery:         {:6}, {:9.06}, {:9.06}",
+        sut_stat.delivery_count,
+        sut_stat.delivery_queue_secs / deliveries,
+        sut_stat.delivery_secs / deliveries,
     );
+
+    // Transactions retried by contention in SUT, since SUT started.
+    println!("\n## retry in SUT    retried , exhausted");
     println!(
-        "customer_by_name: {:6}, {:9.06}, {:9.06}, {:9.06}, {:9.06}",
-        perf[5].counts(),
-        perf[5].avg_e2e(),
-        perf[5].avg_begin(),
-        perf[5].avg_query(),
-        perf[5].avg_commit(),
+        "transactions:     {:8}, {:9}",
+        sut_stat.retry_count, sut_stat.retry_exhausted_count,
     );
 
+    println!("\n##             mix     , minimum");
+    for m in &report.mix {
+        println!(
+            "{:17} {:6.2} %, {:6.2} %",
+            format!("{}:", m.transaction),
+            m.share,
+            m.minimum
+        );
+    }
+
+    print_tables(&report.db_status.tables);
+
+    for warning in &report.warnings {
+        log::warn!("{}", warning);
+    }
+}
+
+/// Write benchmark report to file
+/// CSV has a row for each request type, without SUT database status
+fn write_report(
+    report: &if_types::BenchmarkReport,
+    path: &std::path::Path,
+    format: ReportFormat,
+) -> Result<(), Error> {
+    use std::io::Write;
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut file, report)?,
+        ReportFormat::Csv => {
+            let latencies = ["e2e", "begin", "query", "commit"]
+                .iter()
+                .flat_map(|metric| {
+                    ["avg", "p50", "p90", "p95", "p99", "max"]
+                        .map(|stat| format!("{}_{}", metric, stat))
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                file,
+                "endpoint,scale_factor,concurrent,duration,wait,tpmc,transaction,counts,errors,not_found,server_errors,timeouts,transport_errors,retries,{}",
+                latencies.join(",")
+            )?;
+
+            let params = &report.params;
+            for t in &report.transactions {
+                let latencies = [&t.e2e, &t.begin, &t.query, &t.commit]
+                    .iter()
+                    .flat_map(|l| [l.avg, l.p50, l.p90, l.p95, l.p99, l.max])
+                    .map(|secs| format!("{:.06}", secs))
+                    .collect::<Vec<_>>();
+                writeln!(
+                    file,
+                    "\"{}\",{},{},{},{},{:.1},{},{},{},{},{},{},{},{},{}",
+                    params.endpoint.replace('"', "\"\""),
+                    params.scale_factor,
+                    params.concurrent,
+                    params.duration,
+                    params.wait,
+                    report.tpmc,
+                    t.name,
+                    t.counts,
+                    t.errors,
+                    t.not_found,
+                    t.server_errors,
+                    t.timeouts,
+                    t.transport_errors,
+                    t.retries,
+                    latencies.join(",")
+                )?;
+            }
+        }
+    }
+    file.flush()?;
+
     Ok(())
 }
 
-async fn benchmark_single_terminal(
+/// Print throughput and latency of each interval, while benchmark is running
+async fn print_progress(
+    period: &BenchmarkPeriod,
+    interval: std::time::Duration,
+    perf: &[PerfSummary; 7],
+    abort: &RunAbort,
+) {
+    for index in 0.. {
+        let interval_end = period.launch_t + interval * (index + 1);
+        if period.term_t < interval_end {
+            break;
+        }
+        let until = tokio::time::Instant::from_std(interval_end);
+        if tokio::time::timeout_at(until, abort.aborted())
+            .await
+            .is_ok()
+        {
+            break;
+        }
+
+        let mut total = IntervalSample::default();
+        for summary in perf {
+            let sample = summary.sample(index as usize);
+            total.counts += sample.counts;
+            total.errors += sample.errors;
+            total.e2e_total_us += sample.e2e_total_us;
+            total.e2e_max_us = total.e2e_max_us.max(sample.e2e_max_us);
+        }
+        let new_orders =
+            perf[0].sample(index as usize).counts + perf[6].sample(index as usize).counts;
+        let secs = interval.as_secs_f64();
+        log::info!(
+            "{:7.1}s {:11} : {:8.1} tpm, {:7.1} calls/s, e2e avg {:.06}s max {:.06}s, {} errors",
+            secs * (index + 1) as f64,
+            period.phase(period.launch_t + interval * index),
+            new_orders as f64 * 60.0 / secs,
+            total.counts as f64 / secs,
+            total.e2e_total_us as f64 / total.counts.max(1) as f64 * 0.000_001,
+            total.e2e_max_us as f64 * 0.000_001,
+            total.errors,
+        );
+    }
+}
+
+/// Throughput and latency of each interval, for each request type
+fn time_series(
+    period: &BenchmarkPeriod,
+    interval: std::time::Duration,
+    perf: &[PerfSummary; 7],
+) -> Vec<if_types::TimeSeriesSample> {
+    let names = [
+        "new_order",
+        "payment",
+        "order_status",
+        "delivery",
+        "stock_level",
+        "customer_by_name",
+        "new_order(rb)",
+    ];
+    let intervals = perf.iter().map(|p| p.sample_count()).max().unwrap_or(0);
+    let secs = interval.as_secs_f64();
+
+    (0..intervals)
+        .flat_map(|index| {
+            let phase = period.phase(period.launch_t + interval * index as u32);
+            names.iter().zip(perf).map(move |(name, summary)| {
+                let sample = summary.sample(index);
+                if_types::TimeSeriesSample {
+                    time: secs * index as f64,
+                    phase: phase.to_string(),
+                    transaction: name.to_string(),
+                    counts: sample.counts,
+                    errors: sample.errors,
+                    per_minute: sample.counts as f64 * 60.0 / secs,
+                    e2e_avg: sample.e2e_total_us as f64 / sample.counts.max(1) as f64 * 0.000_001,
+                    e2e_max: sample.e2e_max_us as f64 * 0.000_001,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Write time series to file
+fn write_time_series(
+    samples: &[if_types::TimeSeriesSample],
+    path: &std::path::Path,
+    format: ReportFormat,
+) -> Result<(), Error> {
+    use std::io::Write;
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut file, samples)?,
+        ReportFormat::Csv => {
+            writeln!(
+                file,
+                "time,phase,transaction,counts,errors,per_minute,e2e_avg,e2e_max"
+            )?;
+            for sample in samples {
+                writeln!(
+                    file,
+                    "{:.3},{},{},{},{},{:.1},{:.06},{:.06}",
+                    sample.time,
+                    sample.phase,
+                    sample.transaction,
+                    sample.counts,
+                    sample.errors,
+                    sample.per_minute,
+                    sample.e2e_avg,
+                    sample.e2e_max,
+                )?;
+            }
+        }
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Benchmark time period
+///   ramp-up : launch_t .. start_t
+///   measurement : start_t .. end_t
+///   ramp-down : end_t .. term_t
+#[derive(Clone, Copy)]
+struct BenchmarkPeriod {
+    launch_t: std::time::Instant,
     start_t: std::time::Instant,
     end_t: std::time::Instant,
     term_t: std::time::Instant,
+}
+
+impl BenchmarkPeriod {
+    /// Whether `t` is in measurement interval
+    fn in_measurement(&self, t: std::time::Instant) -> bool {
+        self.start_t <= t && t < self.end_t
+    }
+
+    /// Name of the phase at `t`
+    fn phase(&self, t: std::time::Instant) -> &'static str {
+        if t < self.start_t {
+            "ramp_up"
+        } else if t < self.end_t {
+            "measurement"
+        } else {
+            "ramp_down"
+        }
+    }
+}
+
+/// Emulated terminal
+/// TPC-C standard spec. 4.2.2, home warehouse and district are fixed for each terminal
+struct Terminal {
+    terminal_id: i32,
     warehouse_id: i32,
-    perf: &[PerfSummary; 6],
+    district_id: i32,
+}
+
+/// Benchmark parameters common to all terminals
+struct TerminalConfig {
+    warehouse_count: i32,
+    wait: f64,
+    mix: TransactionMix,
+    /// Master seed, each terminal uses the stream of its terminal id
+    seed: Option<u64>,
+    /// C_RUN of NURand
+    nurand: tpcc_rand::NURandConstants,
+    /// Abort run when failed transactions exceed this percentage
+    max_error_rate: Option<f64>,
+}
+
+/// Transactions completed before error rate is checked,
+/// not to abort run by a few failures at launch
+const MIN_TRANSACTIONS_FOR_ERROR_RATE: usize = 100;
+
+/// Random stream of C_RUN, terminal ids start from 1
+const RUN_CONSTANTS_STREAM: u64 = 0;
+
+/// Stop of all terminals before the end of run, with the reason
+struct RunAbort(tokio::sync::watch::Sender<Option<String>>);
+
+impl RunAbort {
+    fn new() -> Self {
+        RunAbort(tokio::sync::watch::Sender::new(None))
+    }
+
+    /// Stop all terminals, the first reason is kept
+    fn abort(&self, reason: String) {
+        self.0.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(reason);
+            true
+        });
+    }
+
+    fn reason(&self) -> Option<String> {
+        self.0.borrow().clone()
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    /// Wait until aborted
+    async fn aborted(&self) {
+        let _ = self.0.subscribe().wait_for(Option::is_some).await;
+    }
+}
+
+async fn benchmark_single_terminal(
+    period: &BenchmarkPeriod,
+    terminal: &Terminal,
+    config: &TerminalConfig,
+    perf: &[PerfSummary; 7],
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
-) -> Result<i32, Error> {
-    let mut counts = 0u32;
-    let mut new_orders = 0;
-    let mut rand = tpcc_rand::TpcRandom::new();
-
-    while std::time::Instant::now() < term_t {
-        // 5.2.3
-        // Mix of each transaction
-        match counts % 25 {
-            0 | 2 | 4 | 6 | 9 | 11 | 13 | 15 | 18 | 20 | 22 => {
-                // 44%
-                new_order_req(warehouse_id, &perf[0], &endpoints, &client, &mut rand).await?;
-                // Only count up in benchmark period (excludes ramp-up, ramp-down)
-                let now = std::time::Instant::now();
-                if start_t <= now && now < end_t {
-                    new_orders += 1;
-                }
+    abort: &RunAbort,
+) -> Result<(), Error> {
+    let mut rand = match config.seed {
+        Some(seed) => tpcc_rand::TpcRandom::from_seed_stream(seed, terminal.terminal_id as u64),
+        None => tpcc_rand::TpcRandom::new(),
+    }
+    .with_nurand(config.nurand);
+    let mut deck = TransactionDeck::new(&config.mix);
+
+    while std::time::Instant::now() < period.term_t && !abort.is_aborted() {
+        // 5.2.4.2
+        // Select transaction from shuffled deck
+        let transaction = deck.draw(&mut rand);
+
+        // 5.2.5.2, keying time
+        wait_secs(transaction.keying_time() * config.wait, period, abort).await;
+        if period.term_t <= std::time::Instant::now() || abort.is_aborted() {
+            break;
+        }
+
+        let (result, perf_t) = match transaction {
+            TransactionType::NewOrder => {
+                let result = new_order_req(
+                    terminal,
+                    config.warehouse_count,
+                    &endpoints,
+                    &client,
+                    &mut rand,
+                )
+                .await;
+                (result, &perf[0])
             }
-            1 | 3 | 5 | 7 | 10 | 12 | 14 | 16 | 19 | 21 | 23 => {
-                // 44%
-                payment_req(
-                    warehouse_id,
-                    &perf[1],
+            TransactionType::Payment => {
+                let result = payment_req(
+                    terminal,
+                    config.warehouse_count,
                     &perf[5],
                     &endpoints,
                     &client,
                     &mut rand,
                 )
-                .await?;
+                .await;
+                (result, &perf[1])
             }
-            8 => {
-                // 4%
-                order_status_req(
-                    warehouse_id,
-                    &perf[2],
+            TransactionType::OrderStatus => {
+                let result = order_status_req(
+                    terminal.warehouse_id,
                     &perf[5],
                     &endpoints,
                     &client,
                     &mut rand,
                 )
-                .await?;
+                .await;
+                (result, &perf[2])
+            }
+            TransactionType::Delivery => {
+                let result =
+                    delivery_req(terminal.warehouse_id, &endpoints, &client, &mut rand).await;
+                (result, &perf[3])
+            }
+            TransactionType::StockLevel => {
+                let result = stock_level_req(
+                    terminal.warehouse_id,
+                    terminal.district_id,
+                    &endpoints,
+                    &client,
+                    &mut rand,
+                )
+                .await;
+                (result, &perf[4])
             }
-            17 => {
-                // 4%
-                delivery_req(warehouse_id, &perf[3], &endpoints, &client, &mut rand).await?;
+        };
+
+        match result {
+            Ok(completed) => {
+                // Rolled back New-Order transactions are recorded separately
+                let perf_t = match completed.outcome {
+                    Outcome::Success => perf_t,
+                    Outcome::ExpectedRollback => &perf[6],
+                };
+                perf_t.add(&completed.perf, completed.e2e);
             }
-            24 => {
-                // 4%
-                stock_level_req(warehouse_id, &perf[4], &endpoints, &client, &mut rand).await?;
+            // Failures are counted and the terminal continues, unless error rate is exceeded
+            Err(e) => {
+                let failure = Failure::of_error(&e);
+                log::warn!("{} failed, {} : {}", transaction.name(), failure.name(), e);
+                perf_t.add_failure(failure);
+
+                if let Some(max_error_rate) = config.max_error_rate {
+                    if let Some(error_rate) = error_rate(perf) {
+                        if max_error_rate < error_rate {
+                            abort.abort(format!(
+                                "error rate {:.2}% exceeds max-error-rate {}%, run aborted",
+                                error_rate, max_error_rate
+                            ));
+                            break;
+                        }
+                    }
+                }
             }
-            _ => {}
         }
-        counts += 1
+
+        // 5.2.5.4, think time
+        wait_secs(
+            rand.think_time(transaction.think_time_mean()) * config.wait,
+            period,
+            abort,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Classified result of transaction completed by SUT
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    /// New-Order rolled back by unused item number, TPC-C standard spec. 2.4.2.3
+    ExpectedRollback,
+}
+
+/// Transaction completed by SUT, recorded by terminal
+struct Completed {
+    outcome: Outcome,
+    perf: if_types::PerformanceMetrics,
+    e2e: std::time::Duration,
+}
+
+impl Completed {
+    fn success(perf: if_types::PerformanceMetrics, e2e: std::time::Duration) -> Self {
+        Completed {
+            outcome: Outcome::Success,
+            perf,
+            e2e,
+        }
+    }
+}
+
+/// Classified error of transaction request, counted as failure
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Failure {
+    /// 404 from SUT, or no customer found by last name
+    NotFound,
+    /// Other error status from SUT
+    ServerError,
+    /// No response within request timeout
+    Timeout,
+    /// Failed to connect, send request or read response
+    TransportError,
+}
+
+impl Failure {
+    /// Classify error of request helper
+    fn of_error(e: &Error) -> Self {
+        if e.is::<CustomerNotFound>() {
+            return Failure::NotFound;
+        }
+        match e.downcast_ref::<reqwest::Error>() {
+            Some(e) if e.is_timeout() => Failure::Timeout,
+            Some(e) => match e.status() {
+                Some(reqwest::StatusCode::NOT_FOUND) => Failure::NotFound,
+                Some(_) => Failure::ServerError,
+                None => Failure::TransportError,
+            },
+            None => Failure::TransportError,
+        }
     }
-    Ok(new_orders)
+
+    fn name(&self) -> &'static str {
+        match self {
+            Failure::NotFound => "not found",
+            Failure::ServerError => "server error",
+            Failure::Timeout => "timeout",
+            Failure::TransportError => "transport error",
+        }
+    }
+}
+
+/// No customer with the last name in the district
+#[derive(thiserror::Error, Debug)]
+#[error("no customer named {lastname} in warehouse {warehouse_id}, district {district_id}")]
+struct CustomerNotFound {
+    warehouse_id: i32,
+    district_id: i32,
+    lastname: String,
+}
+
+/// Failed transactions in percent of all transactions since launch,
+/// None until enough transactions are completed
+fn error_rate(perf: &[PerfSummary; 7]) -> Option<f64> {
+    // customer_by_name is a part of Payment or Order-Status
+    let transactions = [&perf[0], &perf[6], &perf[1], &perf[2], &perf[3], &perf[4]];
+    let errors = transactions.iter().map(|p| p.total_errors()).sum::<usize>();
+    let total = transactions.iter().map(|p| p.total_counts()).sum::<usize>() + errors;
+    (MIN_TRANSACTIONS_FOR_ERROR_RATE <= total).then(|| 100.0 * errors as f64 / total as f64)
+}
+
+/// Transaction types of TPC-C
+#[derive(Clone, Copy)]
+enum TransactionType {
+    NewOrder,
+    Payment,
+    OrderStatus,
+    Delivery,
+    StockLevel,
+}
+
+impl TransactionType {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::NewOrder => "new_order",
+            Self::Payment => "payment",
+            Self::OrderStatus => "order_status",
+            Self::Delivery => "delivery",
+            Self::StockLevel => "stock_level",
+        }
+    }
+
+    /// Minimum percentage in transaction mix
+    /// TPC-C standard spec. 5.2.3
+    fn min_mix(&self) -> f64 {
+        match self {
+            Self::NewOrder => 0.0,
+            Self::Payment => 43.0,
+            Self::OrderStatus => 4.0,
+            Self::Delivery => 4.0,
+            Self::StockLevel => 4.0,
+        }
+    }
+
+    /// Keying time in secs
+    /// TPC-C standard spec. 5.2.5.7
+    fn keying_time(&self) -> f64 {
+        match self {
+            Self::NewOrder => 18.0,
+            Self::Payment => 3.0,
+            Self::OrderStatus => 2.0,
+            Self::Delivery => 2.0,
+            Self::StockLevel => 2.0,
+        }
+    }
+
+    /// Mean of think time distribution in secs
+    /// TPC-C standard spec. 5.2.5.7
+    fn think_time_mean(&self) -> f64 {
+        match self {
+            Self::NewOrder => 12.0,
+            Self::Payment => 12.0,
+            Self::OrderStatus => 10.0,
+            Self::Delivery => 5.0,
+            Self::StockLevel => 5.0,
+        }
+    }
+
+    /// Limit of 90th percentile response time in secs
+    /// TPC-C standard spec. 5.2.5.3
+    fn response_time_limit(&self) -> f64 {
+        match self {
+            Self::NewOrder => 5.0,
+            Self::Payment => 5.0,
+            Self::OrderStatus => 5.0,
+            Self::Delivery => 5.0,
+            Self::StockLevel => 20.0,
+        }
+    }
+}
+
+/// Number of cards in 100 cards deck for each transaction type
+struct TransactionMix {
+    new_order: u32,
+    payment: u32,
+    order_status: u32,
+    delivery: u32,
+    stock_level: u32,
+}
+
+impl TryFrom<&RunArgs> for TransactionMix {
+    type Error = Error;
+    fn try_from(args: &RunArgs) -> Result<Self, Self::Error> {
+        // Each weight is limited to 100 by clap, the sum does not overflow
+        let others = args.payment_weight
+            + args.order_status_weight
+            + args.delivery_weight
+            + args.stock_level_weight;
+        if 100 <= others {
+            return Err(format!(
+                "Transaction mix leaves no room for New-Order, {}% in total",
+                others
+            )
+            .into());
+        }
+
+        Ok(Self {
+            new_order: 100 - others,
+            payment: args.payment_weight,
+            order_status: args.order_status_weight,
+            delivery: args.delivery_weight,
+            stock_level: args.stock_level_weight,
+        })
+    }
+}
+
+/// Deck of cards to select transaction type
+/// TPC-C standard spec. 5.2.4.2
+struct TransactionDeck {
+    cards: Vec<TransactionType>,
+    next: usize,
+}
+
+impl TransactionDeck {
+    fn new(mix: &TransactionMix) -> Self {
+        let cards = [
+            (TransactionType::NewOrder, mix.new_order),
+            (TransactionType::Payment, mix.payment),
+            (TransactionType::OrderStatus, mix.order_status),
+            (TransactionType::Delivery, mix.delivery),
+            (TransactionType::StockLevel, mix.stock_level),
+        ]
+        .into_iter()
+        .flat_map(|(transaction, n)| std::iter::repeat_n(transaction, n as usize))
+        .collect::<Vec<_>>();
+
+        // Shuffle at first draw
+        let next = cards.len();
+        Self { cards, next }
+    }
+
+    /// Draw next card, re-shuffle when all cards are drawn
+    fn draw(&mut self, rand: &mut tpcc_rand::TpcRandom) -> TransactionType {
+        if self.cards.len() <= self.next {
+            rand.shuffle(&mut self.cards);
+            self.next = 0;
+        }
+        let transaction = self.cards[self.next];
+        self.next += 1;
+        transaction
+    }
+}
+
+/// Sleep in keying time or think time, but wake up at the end of benchmark
+async fn wait_secs(secs: f64, period: &BenchmarkPeriod, abort: &RunAbort) {
+    if secs <= 0.0 {
+        return;
+    }
+    // Wait over the end of run, or too long for Instant, ends at the end of run
+    let until = std::time::Duration::try_from_secs_f64(secs)
+        .ok()
+        .and_then(|secs| std::time::Instant::now().checked_add(secs))
+        .map_or(period.term_t, |until| until.min(period.term_t));
+    let until = tokio::time::Instant::from_std(until);
+    // Wake up by abort, not to wait until the end of think time
+    let _ = tokio::time::timeout_at(until, abort.aborted()).await;
 }
 
 /// New-Order Transaction
 /// TPC-C standard spec. 2.4
 async fn new_order_req(
-    warehouse_id: i32,
-    perf: &PerfSummary,
+    terminal: &Terminal,
+    warehouse_count: i32,
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
     rand: &mut tpcc_rand::TpcRandom,
-) -> Result<bool, Error> {
+) -> Result<Completed, Error> {
+    let warehouse_id = terminal.warehouse_id;
+
     // 2.4.1.3
     let item_count = rand.i32_range(5..=15);
     // 2.4.1.5
     let items = (0..item_count)
-        .map(|_| if_types::NewOrderRequestItem {
-            item_id: rand.non_uniform_i32(8191, 1..=100000),
-            quantity: rand.i32_range(1..=10),
+        .map(|_| {
+            let item_id = rand.item_id();
+            // 1% of items are supplied from remote warehouse
+            let supply_warehouse_id = if 1 < warehouse_count && rand.i32_range(1..=100) == 1 {
+                remote_warehouse_id(warehouse_id, warehouse_count, rand)
+            } else {
+                warehouse_id
+            };
+            if_types::NewOrderRequestItem {
+                item_id,
+                supply_warehouse_id,
+                quantity: rand.i32_range(1..=10),
+            }
         })
         .collect::<Vec<_>>();
 
     // 2.4.1.2
     let req = if_types::NewOrderRequest {
-        terminal_id: warehouse_id,
+        terminal_id: terminal.terminal_id,
         warehouse_id,
         district_id: rand.i32_range(1..=10),
-        customer_id: rand.non_uniform_i32(1023, 1..=3000),
+        customer_id: rand.customer_id(),
         items,
         // 2.4.1.4, rollback in 1/100 transaction
         inject_rollback: rand.i32_range(0..=99) == 0,
@@ -335,59 +1591,75 @@ async fn new_order_req(
     let t = std::time::Instant::now();
     let resp = client.post(endpoints.new_order()).json(&req).send().await?;
 
-    let resp = resp.json::<if_types::NewOrderResponse>().await?;
+    if req.inject_rollback && resp.status() == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+        // 2.4.2.3, expected rollback by unused item number
+        let resp = resp.json::<if_types::NewOrderRollbackResponse>().await?;
+        let elapsed = t.elapsed();
+
+        log::debug!(
+            "New-Order rolled back in {:.03}s, {}",
+            elapsed.as_secs_f32(),
+            resp.contents.message
+        );
+
+        return Ok(Completed {
+            outcome: Outcome::ExpectedRollback,
+            perf: resp.perf,
+            e2e: elapsed,
+        });
+    }
+
+    let resp = resp
+        .error_for_status()?
+        .json::<if_types::NewOrderResponse>()
+        .await?;
     let elapsed = t.elapsed();
 
-    perf.add(&resp.perf, elapsed);
     log::debug!("New-Order succeeded in {:.03}s", elapsed.as_secs_f32());
 
-    Ok(true)
+    Ok(Completed::success(resp.perf, elapsed))
 }
 
 /// Payment Transaction
 /// TPC-C standard spec. 2.5
 async fn payment_req(
-    warehouse_id: i32,
-    perf: &PerfSummary,
+    terminal: &Terminal,
+    warehouse_count: i32,
     perf_c: &PerfSummary,
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
     rand: &mut tpcc_rand::TpcRandom,
-) -> Result<bool, Error> {
+) -> Result<Completed, Error> {
+    let warehouse_id = terminal.warehouse_id;
+
     // 2.5.1.2
     let district_id = rand.i32_range(1..=10);
 
-    let (c_w_id, c_d_id) = if rand.i32_range(1..=100) <= 85 {
-        // home district
+    let (c_w_id, c_d_id) = if rand.i32_range(1..=100) <= 85 || warehouse_count <= 1 {
+        // home warehouse
         (warehouse_id, district_id)
     } else {
-        // remote district
-        // ToDo: random remote warehouse ID
-        (warehouse_id, rand.i32_range(1..=10))
+        // remote warehouse
+        (
+            remote_warehouse_id(warehouse_id, warehouse_count, rand),
+            rand.i32_range(1..=10),
+        )
     };
 
     let c_id = if rand.i32_range(1..=100) <= 60 {
         // by name
-        let name_idx = rand.non_uniform_i32(255, 0..=999);
+        let name_idx = rand.last_name_index();
         let lastname = tpcc_rand::TpcRandom::last_name(name_idx);
 
-        customer_id_by_lastname(
-            warehouse_id,
-            district_id,
-            lastname,
-            perf_c,
-            endpoints,
-            client,
-        )
-        .await?
+        customer_id_by_lastname(c_w_id, c_d_id, lastname, perf_c, endpoints, client).await?
     } else {
         // by id
-        rand.non_uniform_i32(1023, 1..=3000)
+        rand.customer_id()
     };
 
     let req = if_types::PaymentRequest {
-        terminal_id: warehouse_id,
-        warehouse_id: warehouse_id,
+        terminal_id: terminal.terminal_id,
+        warehouse_id,
         district_id,
         customer_warehouse_id: c_w_id,
         customer_district_id: c_d_id,
@@ -405,28 +1677,26 @@ async fn payment_req(
         .await?;
     let elapsed = t.elapsed();
 
-    perf.add(&resp.perf, elapsed);
     log::debug!("Payment succeeded in {:.03}s", elapsed.as_secs_f32());
 
-    Ok(true)
+    Ok(Completed::success(resp.perf, elapsed))
 }
 
 /// Order-Status Transaction
 /// TPC-C standard spec. 2.6
 async fn order_status_req(
     warehouse_id: i32,
-    perf: &PerfSummary,
     perf_c: &PerfSummary,
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
     rand: &mut tpcc_rand::TpcRandom,
-) -> Result<bool, Error> {
+) -> Result<Completed, Error> {
     // 2.6.1.2
     let district_id = rand.i32_range(1..=10);
 
     let c_id = if rand.i32_range(1..=100) <= 60 {
         // by name
-        let name_idx = rand.non_uniform_i32(255, 0..=999);
+        let name_idx = rand.last_name_index();
         let lastname = tpcc_rand::TpcRandom::last_name(name_idx);
 
         customer_id_by_lastname(
@@ -440,7 +1710,7 @@ async fn order_status_req(
         .await?
     } else {
         // by id
-        rand.non_uniform_i32(1023, 1..=3000)
+        rand.customer_id()
     };
 
     let t = std::time::Instant::now();
@@ -455,25 +1725,23 @@ async fn order_status_req(
         .await?;
     let elapsed = t.elapsed();
 
-    perf.add(&resp.perf, elapsed);
     log::debug!(
         "Order-Status succeeded in {:.03}s, {} order found.",
         elapsed.as_secs_f32(),
         resp.contents.orders.len()
     );
 
-    Ok(true)
+    Ok(Completed::success(resp.perf, elapsed))
 }
 
 /// Delivery Transaction
 /// TPC-C standard spec. 2.7
 async fn delivery_req(
     warehouse_id: i32,
-    perf: &PerfSummary,
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
     rand: &mut tpcc_rand::TpcRandom,
-) -> Result<i32, Error> {
+) -> Result<Completed, Error> {
     // 2.7.1.2
     let carrier_id = rand.i32_range(1..=10);
     let req = if_types::DeliveryRequest {
@@ -490,50 +1758,48 @@ async fn delivery_req(
         .await?;
     let elapsed = t.elapsed();
 
-    perf.add(&resp.perf, elapsed);
     log::debug!(
-        "Delivery succeeded in {:.03}s, {} orders delivered.",
+        "Delivery queued in {:.03}s, as delivery {}.",
         elapsed.as_secs_f32(),
-        resp.contents.deliverd_orders
+        resp.contents.delivery_id
     );
 
-    Ok(resp.contents.deliverd_orders)
+    Ok(Completed::success(resp.perf, elapsed))
 }
 
 /// Stock-Level Transaction
 /// TPC-C standard spec. 2.8
 async fn stock_level_req(
     warehouse_id: i32,
-    perf: &PerfSummary,
+    district_id: i32,
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
     rand: &mut tpcc_rand::TpcRandom,
-) -> Result<bool, Error> {
-    for district_id in 1..=10 {
-        let stock_level = rand.i32_range(10..=20);
-
-        let t = std::time::Instant::now();
-        let resp = client
-            .get(endpoints.check_stocks(warehouse_id, district_id))
-            .query(&if_types::StockLevelParams { stock_level })
-            .send()
-            .await?;
+) -> Result<Completed, Error> {
+    // 2.8.1.2
+    let stock_level = rand.i32_range(10..=20);
 
-        let resp = resp
-            .error_for_status()?
-            .json::<if_types::StockLevelResponse>()
-            .await?;
-        let elapsed = t.elapsed();
+    let t = std::time::Instant::now();
+    let resp = client
+        .get(endpoints.check_stocks(warehouse_id, district_id))
+        .query(&if_types::StockLevelParams { stock_level })
+        .send()
+        .await?;
 
-        perf.add(&resp.perf, elapsed);
-        log::debug!(
-            "Stock-Level succeeded in {:.03}s, in district {}, {} low stocks found.",
-            elapsed.as_secs_f32(),
-            district_id,
-            resp.contents.low_stocks
-        );
-    }
-    Ok(true)
+    let resp = resp
+        .error_for_status()?
+        .json::<if_types::StockLevelResponse>()
+        .await?;
+    let elapsed = t.elapsed();
+
+    log::debug!(
+        "Stock-Level succeeded in {:.03}s, in district {}, {} low stocks found.",
+        elapsed.as_secs_f32(),
+        district_id,
+        resp.contents.low_stocks
+    );
+
+    Ok(Completed::success(resp.perf, elapsed))
 }
 
 /// Search customer_id by lastname
@@ -552,57 +1818,212 @@ async fn customer_id_by_lastname(
         .query(&if_types::CustomersByLastnameParams {
             warehouse_id,
             district_id,
-            lastname,
+            lastname: lastname.clone(),
         })
         .send()
         .await?;
-    let resp = resp.json::<if_types::CustomersResponse>().await?;
+    let resp = resp
+        .error_for_status()?
+        .json::<if_types::CustomersResponse>()
+        .await?;
     let elapsed = t.elapsed();
     perf.add(&resp.perf, elapsed);
 
+    // TPC-C standard spec. 2.5.2.2, customer at the middle of customers sorted by first name
     let customers = resp.contents.customers;
-    let customer = &customers[customers.len() / 2];
+    let customer = customers.get(customers.len() / 2).ok_or(CustomerNotFound {
+        warehouse_id,
+        district_id,
+        lastname,
+    })?;
     log::debug!("Customer by lastname in {:.03}s", elapsed.as_secs_f32());
 
     Ok(customer.customer_id)
 }
 
-/*
-/// Query benchmark status
+/// Query benchmark status, with rows and sizes of each table if `tables`
 async fn status(
     endpoints: &EndpointUrls,
     client: &reqwest::Client,
+    tables: bool,
 ) -> Result<if_types::DbStatusResponse, Error> {
-    let resp = client.get(endpoints.status()).send().await?;
+    let resp = client
+        .get(endpoints.status())
+        .query(&if_types::DbStatusParams { tables })
+        .send()
+        .await?;
 
-    let status = resp.json::<if_types::DbStatusResponse>().await?;
+    let status = resp
+        .error_for_status()?
+        .json::<if_types::DbStatusResponse>()
+        .await?;
 
     Ok(status)
 }
-*/
 
-#[derive(Default)]
+/// Select warehouse other than home warehouse in random
+fn remote_warehouse_id(
+    warehouse_id: i32,
+    warehouse_count: i32,
+    rand: &mut tpcc_rand::TpcRandom,
+) -> i32 {
+    let w_id = rand.i32_range(1..=(warehouse_count - 1));
+    if w_id < warehouse_id {
+        w_id
+    } else {
+        w_id + 1
+    }
+}
+
+/// Statistics of single request type
 struct PerfSummary {
+    /// Requests completed in measurement interval, TPC-C standard spec. 5.5
+    measurement: PerfStats,
+    /// Requests completed in ramp-up or ramp-down
+    ramp: PerfStats,
+    /// Time series, from `period.launch_t` for each `interval`
+    samples: std::sync::Mutex<Vec<IntervalSample>>,
+    period: BenchmarkPeriod,
+    interval: std::time::Duration,
+}
+
+impl PerfSummary {
+    fn new(period: &BenchmarkPeriod, interval: std::time::Duration) -> Self {
+        Self {
+            measurement: Default::default(),
+            ramp: Default::default(),
+            samples: Default::default(),
+            period: *period,
+            interval,
+        }
+    }
+
+    /// Statistics for requests completed at `t`
+    fn stats(&self, t: std::time::Instant) -> &PerfStats {
+        if self.period.in_measurement(t) {
+            &self.measurement
+        } else {
+            &self.ramp
+        }
+    }
+
+    /// Sample of interval including `t`
+    fn with_sample<F: FnOnce(&mut IntervalSample)>(&self, t: std::time::Instant, f: F) {
+        let elapsed = t.saturating_duration_since(self.period.launch_t);
+        let index = (elapsed.as_secs_f64() / self.interval.as_secs_f64()) as usize;
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() <= index {
+            samples.resize_with(index + 1, Default::default);
+        }
+        f(&mut samples[index])
+    }
+
+    /// Sample of `index`-th interval
+    fn sample(&self, index: usize) -> IntervalSample {
+        let samples = self.samples.lock().unwrap();
+        samples.get(index).cloned().unwrap_or_default()
+    }
+
+    fn sample_count(&self) -> usize {
+        self.samples.lock().unwrap().len()
+    }
+
+    fn add(&self, perf: &if_types::PerformanceMetrics, e2e: std::time::Duration) {
+        let now = std::time::Instant::now();
+        self.stats(now).add(perf, e2e);
+
+        let e2e_us = e2e.as_micros() as u64;
+        self.with_sample(now, |sample| {
+            sample.counts += 1;
+            sample.e2e_total_us += e2e_us;
+            sample.e2e_max_us = sample.e2e_max_us.max(e2e_us);
+        });
+    }
+
+    fn add_failure(&self, failure: Failure) {
+        let now = std::time::Instant::now();
+        self.stats(now).add_failure(failure);
+        self.with_sample(now, |sample| sample.errors += 1);
+    }
+
+    /// Counts in measurement interval
+    fn counts(&self) -> usize {
+        self.measurement.counts()
+    }
+
+    /// Counts since launch, including ramp-up and ramp-down
+    fn total_counts(&self) -> usize {
+        self.measurement.counts() + self.ramp.counts()
+    }
+
+    /// Errors since launch, including ramp-up and ramp-down
+    fn total_errors(&self) -> usize {
+        use std::sync::atomic::Ordering::Relaxed;
+        self.measurement.errors.load(Relaxed) + self.ramp.errors.load(Relaxed)
+    }
+
+    /// 90th percentile of end-to-end response time in measurement interval, in seconds
+    /// TPC-C standard spec. 5.2.5.3
+    fn p90_e2e(summaries: &[&Self]) -> f64 {
+        let mut e2e = hdrhistogram::Histogram::<u64>::new(3).unwrap();
+        for summary in summaries {
+            let _ = e2e.add(&summary.measurement.histograms.lock().unwrap().e2e);
+        }
+        e2e.value_at_quantile(0.90) as f64 * 0.000_001
+    }
+}
+
+#[derive(Default)]
+struct PerfStats {
     counts: std::sync::atomic::AtomicUsize,
     begin_us: std::sync::atomic::AtomicUsize,
     query_us: std::sync::atomic::AtomicUsize,
     commit_us: std::sync::atomic::AtomicUsize,
     e2e_total_us: std::sync::atomic::AtomicUsize,
+    /// All failures, and failures by kind
+    errors: std::sync::atomic::AtomicUsize,
+    not_found: std::sync::atomic::AtomicUsize,
+    server_errors: std::sync::atomic::AtomicUsize,
+    timeouts: std::sync::atomic::AtomicUsize,
+    transport_errors: std::sync::atomic::AtomicUsize,
+    retries: std::sync::atomic::AtomicUsize,
+    histograms: std::sync::Mutex<PerfHistograms>,
 }
 
-impl PerfSummary {
+impl PerfStats {
     fn add(&self, perf: &if_types::PerformanceMetrics, e2e: std::time::Duration) {
         use std::sync::atomic::Ordering::Relaxed;
 
+        let begin_us = (perf.begin * 1_000_000.0) as usize;
+        let query_us = (perf.query * 1_000_000.0) as usize;
+        let commit_us = (perf.commit * 1_000_000.0) as usize;
+        let e2e_us = e2e.as_micros() as usize;
+
         self.counts.fetch_add(1, Relaxed);
-        self.begin_us
-            .fetch_add((perf.begin * 1_000_000.0) as usize, Relaxed);
-        self.query_us
-            .fetch_add((perf.query * 1_000_000.0) as usize, Relaxed);
-        self.commit_us
-            .fetch_add((perf.commit * 1_000_000.0) as usize, Relaxed);
-        self.e2e_total_us
-            .fetch_add(e2e.as_micros() as usize, Relaxed);
+        self.begin_us.fetch_add(begin_us, Relaxed);
+        self.query_us.fetch_add(query_us, Relaxed);
+        self.commit_us.fetch_add(commit_us, Relaxed);
+        self.e2e_total_us.fetch_add(e2e_us, Relaxed);
+        self.retries.fetch_add(perf.retries as usize, Relaxed);
+
+        // Histograms are auto resized, recording never fails
+        let mut histograms = self.histograms.lock().unwrap();
+        let _ = histograms.e2e.record(e2e_us as u64);
+        let _ = histograms.begin.record(begin_us as u64);
+        let _ = histograms.query.record(query_us as u64);
+        let _ = histograms.commit.record(commit_us as u64);
+    }
+
+    fn add_failure(&self, failure: Failure) {
+        use std::sync::atomic::Ordering::Relaxed;
+        self.errors.fetch_add(1, Relaxed);
+        let by_failure = match failure {
+            Failure::NotFound => &self.not_found,
+            Failure::ServerError => &self.server_errors,
+            Failure::Timeout => &self.timeouts,
+            Failure::TransportError => &self.transport_errors,
+        };
+        by_failure.fetch_add(1, Relaxed);
     }
 
     fn counts(&self) -> usize {
@@ -629,4 +2050,68 @@ impl PerfSummary {
         use std::sync::atomic::Ordering::Relaxed;
         (self.e2e_total_us.load(Relaxed) as f64) / (self.counts.load(Relaxed) as f64) * 0.000_001
     }
+
+    fn to_report(&self, name: &str) -> if_types::TransactionReport {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let histograms = self.histograms.lock().unwrap();
+        if_types::TransactionReport {
+            name: name.to_string(),
+            counts: self.counts() as u64,
+            errors: self.errors.load(Relaxed) as u64,
+            not_found: self.not_found.load(Relaxed) as u64,
+            server_errors: self.server_errors.load(Relaxed) as u64,
+            timeouts: self.timeouts.load(Relaxed) as u64,
+            transport_errors: self.transport_errors.load(Relaxed) as u64,
+            retries: self.retries.load(Relaxed) as u64,
+            e2e: latency_report(&histograms.e2e, self.avg_e2e()),
+            begin: latency_report(&histograms.begin, self.avg_begin()),
+            query: latency_report(&histograms.query, self.avg_query()),
+            commit: latency_report(&histograms.commit, self.avg_commit()),
+        }
+    }
+}
+
+/// Throughput and latency in single interval
+#[derive(Default, Clone)]
+struct IntervalSample {
+    counts: u64,
+    errors: u64,
+    e2e_total_us: u64,
+    e2e_max_us: u64,
+}
+
+/// Response time distribution in micro seconds
+struct PerfHistograms {
+    e2e: hdrhistogram::Histogram<u64>,
+    begin: hdrhistogram::Histogram<u64>,
+    query: hdrhistogram::Histogram<u64>,
+    commit: hdrhistogram::Histogram<u64>,
+}
+
+impl Default for PerfHistograms {
+    fn default() -> Self {
+        // 3 significant digits, auto resized
+        let histogram = || hdrhistogram::Histogram::<u64>::new(3).unwrap();
+        Self {
+            e2e: histogram(),
+            begin: histogram(),
+            query: histogram(),
+            commit: histogram(),
+        }
+    }
+}
+
+/// Latency distribution in seconds
+fn latency_report(histogram: &hdrhistogram::Histogram<u64>, avg: f64) -> if_types::LatencyReport {
+    let secs = |us: u64| us as f64 * 0.000_001;
+    if_types::LatencyReport {
+        // NaN for no calls
+        avg: if avg.is_nan() { 0.0 } else { avg },
+        p50: secs(histogram.value_at_quantile(0.50)),
+        p90: secs(histogram.value_at_quantile(0.90)),
+        p95: secs(histogram.value_at_quantile(0.95)),
+        p99: secs(histogram.value_at_quantile(0.99)),
+        max: secs(histogram.max()),
+    }
 }
@@ -3,13 +3,17 @@
 /// cargo build --release will generate target/release/bootstrap from this lambda_bootstrap.rs,
 /// then compress this bootstrap in ZIP and deploy it to the Lambda function.
 ///
+/// POST /prepare_db does not work on Lambda, the background job is frozen after the response.
+/// Prepare the database by the standalone sut binary with the same DATABASE_URL.
+///
 use sut::*;
 #[tokio::main]
 async fn main() -> Result<(), lambda_http::Error> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
     // On lambda, single thread, single DB connection
-    lambda_http::run(app(1).await).await?;
+    // Background tasks are frozen after response, Delivery is executed before response
+    lambda_http::run(app(1, false).await).await?;
 
     Ok(())
 }
@@ -18,7 +18,7 @@ async fn main() -> Result<(), lambda_http::Error> {
     } else {
         2 * num_cpus::get() as u32
     };
-    axum::serve(listener, app(db_connections).await).await?;
+    axum::serve(listener, app(db_connections, true).await).await?;
 
     Ok(())
 }
@@ -0,0 +1,60 @@
+use crate::SpawnTransaction;
+use axum::extract;
+use if_types::ConsistencyResponse;
+
+/// Check consistency conditions
+/// TPC-C standard spec. 3.3.2
+pub(crate) async fn check_consistency(
+    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+) -> Result<axum::response::Json<ConsistencyResponse>, crate::Error> {
+    let perflog = crate::PerformanceLog::new();
+    let (conditions, mut perflog) = state
+        .spawn_read_transaction(perflog, move |conn, perflog| {
+            perflog.begin();
+
+            let conditions = tpcc_models::consistency::check(conn)?;
+
+            perflog.finish();
+            Ok::<_, crate::Error>(conditions)
+        })
+        .await?;
+
+    perflog.commit();
+    let perf = perflog.to_performance_metric();
+    log::debug!(
+        "check_consistency() : Begin {:.03}s, Query {:.03}s, Commit {:03}s",
+        perf.begin,
+        perf.query,
+        perf.commit
+    );
+
+    let consistent = conditions
+        .iter()
+        .all(|condition| condition.initial_only || condition.is_consistent());
+    for condition in conditions.iter().filter(|c| !c.is_consistent()) {
+        log::warn!(
+            "Consistency condition {} is violated in {} of {} rows",
+            condition.number,
+            condition.violations,
+            condition.checked
+        );
+    }
+
+    let conditions = conditions
+        .into_iter()
+        .map(|condition| if_types::ConsistencyCondition {
+            number: condition.number,
+            description: condition.description.to_string(),
+            initial_only: condition.initial_only,
+            checked: condition.checked,
+            violations: condition.violations,
+            samples: condition.samples,
+        })
+        .collect();
+
+    Ok(axum::Json(ConsistencyResponse {
+        consistent,
+        conditions,
+        perf,
+    }))
+}
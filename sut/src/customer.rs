@@ -10,9 +10,7 @@ pub(crate) async fn customer_by_id(
 
     let perflog = crate::PerformanceLog::new();
     let (contents, mut perflog) = state
-        .pool
-        .spawn_read_transaction(move |conn| {
-            let mut perflog = perflog;
+        .spawn_read_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             // Search customer by ID
@@ -30,12 +28,9 @@ pub(crate) async fn customer_by_id(
             };
 
             perflog.finish();
-            Ok::<_, crate::Error>((
-                if_types::CustomersContents {
-                    customers: vec![customer],
-                },
-                perflog,
-            ))
+            Ok::<_, crate::Error>(if_types::CustomersContents {
+                customers: vec![customer],
+            })
         })
         .await?;
 
@@ -68,9 +63,7 @@ pub(crate) async fn customer_by_lastname(
 
     let perflog = crate::PerformanceLog::new();
     let (contents, mut perflog) = state
-        .pool
-        .spawn_read_transaction(move |conn| {
-            let mut perflog = perflog;
+        .spawn_read_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             // Search customer by lastname
@@ -97,7 +90,7 @@ pub(crate) async fn customer_by_lastname(
                 .collect::<Vec<_>>();
 
             perflog.finish();
-            Ok::<_, crate::Error>((if_types::CustomersContents { customers }, perflog))
+            Ok::<_, crate::Error>(if_types::CustomersContents { customers })
         })
         .await?;
 
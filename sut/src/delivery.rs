@@ -1,55 +1,247 @@
 use crate::SpawnTransaction;
 use axum::extract;
-use if_types::{DeliveryRequest, DeliveryResponse};
+use if_types::{DeliveryRequest, DeliveryResponse, DeliveryStatus, DeliveryStatusResponse};
 
-/// New-Order Transaction
-/// TPC-C standard spec. 2.4
-pub(crate) async fn delivery(
-    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
-    extract::Json(params): extract::Json<DeliveryRequest>,
-) -> Result<axum::response::Json<DeliveryResponse>, crate::Error> {
+/// Number of delivery results kept in memory
+const MAX_DELIVERY_RESULTS: usize = 100_000;
+
+/// Queue of deferred Delivery transactions
+/// TPC-C standard spec. 2.7.2
+pub(crate) struct DeliveryQueue {
+    /// None if Delivery is executed before response, without workers
+    sender: Option<tokio::sync::mpsc::UnboundedSender<QueuedDelivery>>,
+    results: std::sync::Mutex<std::collections::BTreeMap<u64, DeliveryStatusResponse>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+/// Delivery request waiting in the queue
+pub(crate) struct QueuedDelivery {
+    delivery_id: u64,
+    params: DeliveryRequest,
+    queued_at: std::time::Instant,
+}
+
+pub(crate) type DeliveryReceiver = tokio::sync::mpsc::UnboundedReceiver<QueuedDelivery>;
+
+impl DeliveryQueue {
+    /// Queue for workers taking requests from the receiver
+    pub fn new() -> (Self, DeliveryReceiver) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let queue = Self {
+            sender: Some(sender),
+            ..Self::inline()
+        };
+        (queue, receiver)
+    }
+
+    /// Queue recording results only, requests are executed by the handler
+    pub fn inline() -> Self {
+        Self {
+            sender: None,
+            results: std::sync::Mutex::new(std::collections::BTreeMap::new()),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+        }
+    }
+
+    /// Push delivery request to the queue,
+    /// returns the request back if it should be executed by the caller
+    fn enqueue(
+        &self,
+        params: DeliveryRequest,
+    ) -> (if_types::DeliveryContents, Option<QueuedDelivery>) {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let delivery_id = self.next_id.fetch_add(1, Relaxed);
+        let queued_at = chrono::Utc::now();
+        let status = DeliveryStatusResponse {
+            delivery_id,
+            warehouse_id: params.warehouse_id,
+            carrier_id: params.carrier_id,
+            status: DeliveryStatus::Queued,
+            queued_at,
+            completed_at: None,
+            queue_secs: None,
+            perf: None,
+            districts: vec![],
+            error: None,
+        };
+
+        {
+            let mut results = self.results.lock().unwrap();
+            results.insert(delivery_id, status);
+            // Forget old results
+            while MAX_DELIVERY_RESULTS < results.len() {
+                results.pop_first();
+            }
+        }
+
+        let queued = QueuedDelivery {
+            delivery_id,
+            params,
+            queued_at: std::time::Instant::now(),
+        };
+        let contents = if_types::DeliveryContents {
+            delivery_id,
+            queued_at,
+        };
+        match &self.sender {
+            Some(sender) => {
+                // Receiver lives as long as workers, so send() never fails while serving
+                let _ = sender.send(queued);
+                (contents, None)
+            }
+            None => (contents, Some(queued)),
+        }
+    }
+
+    /// Update recorded result
+    fn update<F: FnOnce(&mut DeliveryStatusResponse)>(&self, delivery_id: u64, f: F) {
+        if let Some(status) = self.results.lock().unwrap().get_mut(&delivery_id) {
+            f(status)
+        }
+    }
+}
+
+/// Start workers to execute queued Delivery transactions
+pub(crate) fn spawn_workers(
+    state: std::sync::Arc<super::AppState>,
+    receiver: DeliveryReceiver,
+    workers: usize,
+) {
+    let receiver = std::sync::Arc::new(tokio::sync::Mutex::new(receiver));
+    for _ in 0..workers {
+        let state = state.clone();
+        let receiver = receiver.clone();
+        tokio::spawn(async move {
+            loop {
+                let queued = receiver.lock().await.recv().await;
+                match queued {
+                    Some(queued) => execute_delivery(&state, queued).await,
+                    None => break,
+                }
+            }
+        });
+    }
+}
+
+/// Delivery Transaction, run in deferred mode
+/// TPC-C standard spec. 2.7.4
+async fn execute_delivery(state: &std::sync::Arc<super::AppState>, queued: QueuedDelivery) {
     use std::sync::atomic::Ordering::Relaxed;
 
+    let delivery_id = queued.delivery_id;
+    let queue_time = queued.queued_at.elapsed();
+    state.delivery_queue.update(delivery_id, |status| {
+        status.status = DeliveryStatus::Running;
+        status.queue_secs = Some(queue_time.as_secs_f64());
+    });
+
+    let params = queued.params;
     let perflog = crate::PerformanceLog::new();
-    let (contents, mut perflog) = state
-        .pool
-        .spawn_write_transaction(move |conn| {
-            let mut perflog = perflog;
+    let result = state
+        .spawn_write_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             let warehouse = tpcc_models::Warehouse::find(params.warehouse_id, conn)?;
             let districts = warehouse.all_districts(conn)?;
 
-            let mut total_delivered = 0;
+            let mut delivered = Vec::with_capacity(districts.len());
             for district in &districts {
-                let delivered_orders = district.delivery(params.carrier_id, conn)?;
-                total_delivered += delivered_orders;
+                let order_ids = district.delivery(params.carrier_id, conn)?;
+                let (_, district_id) = district.id();
+                delivered.push(if_types::DeliveryDistrict {
+                    district_id,
+                    skipped: order_ids.is_empty(),
+                    order_ids,
+                });
             }
 
             perflog.finish();
-            Ok::<_, crate::Error>((
-                if_types::DeliveryContents {
-                    deliverd_orders: total_delivered as i32,
-                },
-                perflog,
-            ))
+            Ok::<_, crate::Error>(delivered)
         })
-        .await?;
+        .await;
+
+    match result {
+        Ok((delivered, mut perflog)) => {
+            perflog.commit();
+            let perf = perflog.to_performance_metric();
+            log::debug!(
+                "delivery() : Queue {:.03}s, Begin {:.03}s, Query {:.03}s, Commit {:03}s",
+                queue_time.as_secs_f64(),
+                perf.begin,
+                perf.query,
+                perf.commit
+            );
 
+            state.statistics.delivery_count.fetch_add(1, Relaxed);
+            state
+                .statistics
+                .delivery_us
+                .fetch_add(perflog.total_us(), Relaxed);
+            state
+                .statistics
+                .delivery_queue_us
+                .fetch_add(queue_time.as_micros() as usize, Relaxed);
+
+            state.delivery_queue.update(delivery_id, |status| {
+                status.status = DeliveryStatus::Completed;
+                status.completed_at = Some(chrono::Utc::now());
+                status.perf = Some(perf);
+                status.districts = delivered;
+            });
+        }
+        Err(e) => {
+            log::error!("delivery {} failed : {:?}", delivery_id, e);
+            state.delivery_queue.update(delivery_id, |status| {
+                status.status = DeliveryStatus::Failed;
+                status.completed_at = Some(chrono::Utc::now());
+                status.error = Some(e.to_string());
+            });
+        }
+    }
+}
+
+/// Queue Delivery Transaction, returns acknowledgement immediately
+/// TPC-C standard spec. 2.7.2
+///
+/// Without workers, the queued transaction is executed before the acknowledgement.
+pub(crate) async fn delivery(
+    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+    extract::Json(params): extract::Json<DeliveryRequest>,
+) -> Result<axum::response::Json<DeliveryResponse>, crate::Error> {
+    let mut perflog = crate::PerformanceLog::new();
+    perflog.begin();
+    let (contents, queued) = state.delivery_queue.enqueue(params);
+    perflog.finish();
     perflog.commit();
+
+    if let Some(queued) = queued {
+        execute_delivery(&state, queued).await;
+    }
+
     let perf = perflog.to_performance_metric();
     log::debug!(
-        "delivery() : Begin {:.03}s, Query {:.03}s, Commit {:03}s",
-        perf.begin,
-        perf.query,
-        perf.commit
+        "delivery() : queued as {} in {:.06}s",
+        contents.delivery_id,
+        perf.query
     );
 
-    state.statistics.delivery_count.fetch_add(1, Relaxed);
-    state
-        .statistics
-        .delivery_us
-        .fetch_add(perflog.total_us(), Relaxed);
-
     Ok(axum::Json(DeliveryResponse { contents, perf }))
 }
+
+/// Result of queued Delivery Transaction
+pub(crate) async fn delivery_status(
+    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+    extract::Path(delivery_id): extract::Path<u64>,
+) -> Result<axum::response::Json<DeliveryStatusResponse>, crate::Error> {
+    let status = state
+        .delivery_queue
+        .results
+        .lock()
+        .unwrap()
+        .get(&delivery_id)
+        .cloned()
+        .ok_or(crate::Error::DeliveryNotFound(delivery_id))?;
+
+    Ok(axum::Json(status))
+}
@@ -1,9 +1,11 @@
+mod consistency;
 mod customer;
 mod delivery;
 mod new_order;
 mod order_status;
 mod payment;
 mod perf;
+mod population;
 mod setup;
 mod spawn_transaction;
 mod stock_level;
@@ -12,18 +14,43 @@ use perf::PerformanceLog;
 use spawn_transaction::SpawnTransaction;
 
 /// Initialize & build Axum route
-pub async fn app(db_connectinos: u32) -> axum::Router {
+///
+/// With `deferred_delivery`, Delivery transactions are executed by background workers.
+/// Otherwise, e.g. on Lambda freezing background tasks after response,
+/// each Delivery transaction is executed before its response.
+pub async fn app(db_connectinos: u32, deferred_delivery: bool) -> axum::Router {
     use axum::routing::{get, post};
 
     println!("{} database connections", db_connectinos);
     let db_url = std::env::var("DATABASE_URL").unwrap_or("tpc_c.sqlite".to_string());
-    let pool = tpcc_models::pool(&db_url, db_connectinos)
-        .expect(&format!("Can not open database {}", db_url));
+    let transaction_options = transaction_options();
+    let sqlite_options = sqlite_options();
+    let pool = tpcc_models::pool(
+        &db_url,
+        db_connectinos,
+        &transaction_options,
+        &sqlite_options,
+    )
+    .expect(&format!("Can not open database {}", db_url));
+    let (delivery_queue, delivery_receiver) = if deferred_delivery {
+        let (queue, receiver) = delivery::DeliveryQueue::new();
+        (queue, Some(receiver))
+    } else {
+        (delivery::DeliveryQueue::inline(), None)
+    };
     let app_state = std::sync::Arc::new(AppState {
         pool,
         statistics: perf::Statistics::default(),
+        delivery_queue,
+        retry_policy: spawn_transaction::RetryPolicy::from_env(),
+        prepare_jobs: setup::PrepareJobs::new(),
     });
 
+    // Workers for deferred Delivery transactions
+    if let Some(receiver) = delivery_receiver {
+        delivery::spawn_workers(app_state.clone(), receiver, delivery_workers());
+    }
+
     axum::Router::new()
         .route("/orders", post(new_order::new_order))
         .route("/payment", post(payment::payment))
@@ -37,18 +64,97 @@ pub async fn app(db_connectinos: u32) -> axum::Router {
         )
         .route("/customers", get(customer::customer_by_lastname))
         .route("/delivery", post(delivery::delivery))
+        .route("/delivery/:delivery_id", get(delivery::delivery_status))
         .route(
             "/districts/:warehouse_id/:district_id/check_stocks",
             get(stock_level::check_stocks),
         )
         .route("/prepare_db", post(setup::prepare_db))
+        .route("/prepare_db/:job_id", get(setup::prepare_db_status))
+        .route("/consistency", get(consistency::check_consistency))
+        .route("/population", get(population::check_population))
         .route("/", get(setup::status))
         .with_state(app_state)
 }
 
+/// Workers of deferred Delivery transactions from environment variable
+///   DELIVERY_WORKERS : at least 1 (default 1)
+fn delivery_workers() -> usize {
+    let workers = if let Ok(workers) = std::env::var("DELIVERY_WORKERS") {
+        workers
+            .parse::<usize>()
+            .expect("Can not parse DELIVERY_WORKERS as integer")
+    } else {
+        1
+    };
+    assert!(0 < workers, "DELIVERY_WORKERS must be at least 1");
+    workers
+}
+
+/// Transaction isolation options from environment variables
+///   TX_ISOLATION_LEVEL : read_committed, repeatable_read or serializable (default read_committed)
+///   TX_READ_ONLY : run read transactions as READ ONLY, true or false (default false)
+/// SQLite ignores them, its transaction is always SERIALIZABLE.
+fn transaction_options() -> tpcc_models::TransactionOptions {
+    let isolation_level = if let Ok(level) = std::env::var("TX_ISOLATION_LEVEL") {
+        level
+            .parse::<tpcc_models::IsolationLevel>()
+            .expect("Can not parse TX_ISOLATION_LEVEL")
+    } else {
+        tpcc_models::IsolationLevel::default()
+    };
+    let read_only = if let Ok(read_only) = std::env::var("TX_READ_ONLY") {
+        read_only
+            .parse::<bool>()
+            .expect("Can not parse TX_READ_ONLY as true or false")
+    } else {
+        false
+    };
+
+    tpcc_models::TransactionOptions {
+        isolation_level,
+        read_only,
+    }
+}
+
+/// SQLite PRAGMAs from environment variables
+///   SQLITE_JOURNAL_MODE : delete, truncate, persist, memory, wal, wal2 or off (default delete)
+///   SQLITE_SYNCHRONOUS : off, normal, full or extra (default SQLite default)
+///   SQLITE_CACHE_SIZE : pages if positive, KiB if negative (default -32768, 32MiB)
+///   SQLITE_MMAP_SIZE : bytes of memory mapped I/O (default SQLite default)
+///   SQLITE_TEMP_STORE : default, file or memory (default SQLite default)
+///   SQLITE_LOCKING_MODE : normal or exclusive (default SQLite default), exclusive needs DB_CONN=1
+///   SQLITE_BUSY_TIMEOUT_MS : wait for lock in milli-secs (default 3000)
+fn sqlite_options() -> tpcc_models::SqliteOptions {
+    fn parse_env<T: std::str::FromStr>(name: &str) -> Option<T>
+    where
+        T::Err: std::fmt::Display,
+    {
+        std::env::var(name).ok().map(|value| {
+            value
+                .parse::<T>()
+                .unwrap_or_else(|e| panic!("Can not parse {}: {}", name, e))
+        })
+    }
+
+    let defaults = tpcc_models::SqliteOptions::default();
+    tpcc_models::SqliteOptions {
+        journal_mode: parse_env("SQLITE_JOURNAL_MODE").unwrap_or(defaults.journal_mode),
+        synchronous: parse_env("SQLITE_SYNCHRONOUS").or(defaults.synchronous),
+        cache_size: parse_env("SQLITE_CACHE_SIZE").unwrap_or(defaults.cache_size),
+        mmap_size: parse_env("SQLITE_MMAP_SIZE").or(defaults.mmap_size),
+        temp_store: parse_env("SQLITE_TEMP_STORE").or(defaults.temp_store),
+        locking_mode: parse_env("SQLITE_LOCKING_MODE").or(defaults.locking_mode),
+        busy_timeout: parse_env("SQLITE_BUSY_TIMEOUT_MS").unwrap_or(defaults.busy_timeout),
+    }
+}
+
 struct AppState {
     pool: tpcc_models::Pool,
     statistics: perf::Statistics,
+    delivery_queue: delivery::DeliveryQueue,
+    retry_policy: spawn_transaction::RetryPolicy,
+    prepare_jobs: setup::PrepareJobs,
 }
 
 /// Error type in request handler
@@ -62,6 +168,14 @@ pub enum Error {
     TokioJoinError(#[from] tokio::task::JoinError),
     #[error("database schema setup error")]
     DbMigrationError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("item number is not valid")]
+    ItemNotValid(PerformanceLog),
+    #[error("delivery {0} not found")]
+    DeliveryNotFound(u64),
+    #[error("prepare_db job {0} not found")]
+    PrepareJobNotFound(u64),
+    #[error("prepare_db job {0} is running")]
+    PrepareJobRunning(u64),
 }
 
 impl axum::response::IntoResponse for Error {
@@ -76,6 +190,10 @@ impl axum::response::IntoResponse for Error {
                     StatusCode::INTERNAL_SERVER_ERROR.into_response()
                 }
             },
+            Error::ItemNotValid(_) => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+            Error::DeliveryNotFound(_) => StatusCode::NOT_FOUND.into_response(),
+            Error::PrepareJobNotFound(_) => StatusCode::NOT_FOUND.into_response(),
+            Error::PrepareJobRunning(_) => StatusCode::CONFLICT.into_response(),
             _ => {
                 log::error!("{:?}", self);
                 StatusCode::INTERNAL_SERVER_ERROR.into_response()
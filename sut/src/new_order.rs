@@ -1,23 +1,24 @@
 use crate::SpawnTransaction;
 use axum::extract;
-use if_types::{NewOrderRequest, NewOrderResponse};
+use axum::response::IntoResponse;
+use if_types::{NewOrderRequest, NewOrderResponse, NewOrderRollbackResponse};
 
 /// New-Order Transaction
 /// TPC-C standard spec. 2.4
 pub(crate) async fn new_order(
     extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
     extract::Json(params): extract::Json<NewOrderRequest>,
-) -> Result<axum::response::Json<NewOrderResponse>, crate::Error> {
+) -> Result<axum::response::Response, crate::Error> {
     use std::sync::atomic::Ordering::Relaxed;
 
     let perflog = crate::PerformanceLog::new();
+    let (warehouse_id, district_id, customer_id) =
+        (params.warehouse_id, params.district_id, params.customer_id);
 
-    let (contents, mut perflog) = state
-        .pool
-        .spawn_write_transaction(move |conn| {
+    let result = state
+        .spawn_write_transaction(perflog, move |conn, perflog| {
             use tpcc_models::Warehouse;
 
-            let mut perflog = perflog;
             perflog.begin();
 
             // Transaction described in TPC-C standard spec. 2.4.2
@@ -26,16 +27,27 @@ pub(crate) async fn new_order(
             let customer = district.find_customer(params.customer_id, conn)?;
 
             // Find order items
-            let order_items = params
-                .items
-                .iter()
-                .map(|item| {
-                    // ToDo : random select remote warehouse
-                    let stocked_item =
-                        tpcc_models::StockedItem::find(params.warehouse_id, item.item_id, conn)?;
-                    Ok((stocked_item, item.quantity))
-                })
-                .collect::<Result<Vec<_>, crate::Error>>()?;
+            let last_idx = params.items.len().saturating_sub(1);
+            let mut order_items = Vec::with_capacity(params.items.len());
+            for (idx, item) in params.items.iter().enumerate() {
+                // 2.4.1.5, unused item number in the last line to trigger rollback
+                let item_id = if params.inject_rollback && idx == last_idx {
+                    tpcc_models::UNUSED_ITEM_ID
+                } else {
+                    item.item_id
+                };
+
+                // Stock in home or remote supply warehouse
+                match tpcc_models::StockedItem::find(item.supply_warehouse_id, item_id, conn) {
+                    Ok(stocked_item) => order_items.push((stocked_item, item.quantity)),
+                    Err(tpcc_models::QueryError::NotFound) => {
+                        // 2.4.2.3, "Item number is not valid", rollback whole transaction
+                        perflog.finish();
+                        return Err(crate::Error::ItemNotValid(perflog.clone()));
+                    }
+                    Err(e) => Err(e)?,
+                }
+            }
 
             // Insert into database
             let (order, lines) = district.insert_order(&customer, &order_items, conn)?;
@@ -55,9 +67,47 @@ pub(crate) async fn new_order(
             };
 
             perflog.finish();
-            Ok::<_, crate::Error>((resp, perflog))
+            Ok::<_, crate::Error>(resp)
         })
-        .await?;
+        .await;
+
+    let (contents, mut perflog) = match result {
+        Ok(committed) => committed,
+        Err(crate::Error::ItemNotValid(mut perflog)) => {
+            // Rolled back
+            perflog.commit();
+            let perf = perflog.to_performance_metric();
+            log::debug!(
+                "new_order() rollback : Begin {:.03}s, Query {:.03}s, Rollback {:03}s",
+                perf.begin,
+                perf.query,
+                perf.commit
+            );
+
+            state
+                .statistics
+                .new_order_rollback_count
+                .fetch_add(1, Relaxed);
+            state
+                .statistics
+                .new_order_rollback_us
+                .fetch_add(perflog.total_us(), Relaxed);
+
+            let contents = if_types::NewOrderRollbackContents {
+                warehouse_id,
+                district_id,
+                customer_id,
+                message: "Item number is not valid".to_string(),
+            };
+            let resp = NewOrderRollbackResponse { contents, perf };
+            return Ok((
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                axum::Json(resp),
+            )
+                .into_response());
+        }
+        Err(e) => return Err(e),
+    };
 
     perflog.commit();
     let perf = perflog.to_performance_metric();
@@ -74,5 +124,5 @@ pub(crate) async fn new_order(
         .new_order_us
         .fetch_add(perflog.total_us(), Relaxed);
 
-    Ok(axum::Json(NewOrderResponse { contents, perf }))
+    Ok(axum::Json(NewOrderResponse { contents, perf }).into_response())
 }
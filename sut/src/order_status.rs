@@ -12,9 +12,7 @@ pub(crate) async fn order_status(
 
     let perflog = crate::PerformanceLog::new();
     let (contents, mut perflog) = state
-        .pool
-        .spawn_read_transaction(move |conn| {
-            let mut perflog = perflog;
+        .spawn_read_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             // Search customer by ID
@@ -46,16 +44,13 @@ pub(crate) async fn order_status(
                     };
 
                     perflog.finish();
-                    Ok::<_, crate::Error>((
-                        if_types::OrderStatusContents {
-                            orders: vec![order],
-                        },
-                        perflog,
-                    ))
+                    Ok::<_, crate::Error>(if_types::OrderStatusContents {
+                        orders: vec![order],
+                    })
                 }
                 Err(tpcc_models::QueryError::NotFound) => {
                     perflog.finish();
-                    Ok((if_types::OrderStatusContents { orders: vec![] }, perflog))
+                    Ok(if_types::OrderStatusContents { orders: vec![] })
                 }
                 Err(e) => Err(e)?,
             }
@@ -12,9 +12,7 @@ pub(crate) async fn payment(
 
     let perflog = crate::PerformanceLog::new();
     let (contents, mut perflog) = state
-        .pool
-        .spawn_write_transaction(move |conn| {
-            let mut perflog = perflog;
+        .spawn_write_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             // Search district, customer by ID
@@ -32,13 +30,10 @@ pub(crate) async fn payment(
                 customer.pay(&district, params.amount, conn)?;
 
             perflog.finish();
-            Ok::<_, crate::Error>((
-                if_types::PaymentContents {
-                    amount: params.amount,
-                    paied_at: history.timestamp().and_utc(),
-                },
-                perflog,
-            ))
+            Ok::<_, crate::Error>(if_types::PaymentContents {
+                amount: params.amount,
+                paied_at: history.timestamp().and_utc(),
+            })
         })
         .await?;
 
@@ -1,7 +1,9 @@
 use if_types::PerformanceMetrics;
 
-pub(crate) struct PerformanceLog {
+#[derive(Debug, Clone)]
+pub struct PerformanceLog {
     tm: [std::time::Instant; 4],
+    retries: u32,
 }
 
 impl PerformanceLog {
@@ -11,6 +13,7 @@ impl PerformanceLog {
 
         Self {
             tm: [now, now, now, now],
+            retries: 0,
         }
     }
 
@@ -29,6 +32,11 @@ impl PerformanceLog {
         self.tm[3] = std::time::Instant::now();
     }
 
+    /// Number of retries before successful attempt
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
     /// Total time
     pub fn total_us(&self) -> usize {
         (self.tm[3] - self.tm[0]).as_micros() as usize
@@ -40,6 +48,7 @@ impl PerformanceLog {
             begin: (self.tm[1] - self.tm[0]).as_secs_f64(),
             query: (self.tm[2] - self.tm[1]).as_secs_f64(),
             commit: (self.tm[3] - self.tm[2]).as_secs_f64(),
+            retries: self.retries,
         }
     }
 }
@@ -49,18 +58,23 @@ impl PerformanceLog {
 pub(crate) struct Statistics {
     pub(crate) new_order_count: std::sync::atomic::AtomicUsize,
     pub(crate) new_order_us: std::sync::atomic::AtomicUsize,
+    pub(crate) new_order_rollback_count: std::sync::atomic::AtomicUsize,
+    pub(crate) new_order_rollback_us: std::sync::atomic::AtomicUsize,
     pub(crate) payment_count: std::sync::atomic::AtomicUsize,
     pub(crate) payment_us: std::sync::atomic::AtomicUsize,
     pub(crate) order_status_count: std::sync::atomic::AtomicUsize,
     pub(crate) order_status_us: std::sync::atomic::AtomicUsize,
     pub(crate) delivery_count: std::sync::atomic::AtomicUsize,
     pub(crate) delivery_us: std::sync::atomic::AtomicUsize,
+    pub(crate) delivery_queue_us: std::sync::atomic::AtomicUsize,
     pub(crate) stock_level_count: std::sync::atomic::AtomicUsize,
     pub(crate) stock_level_us: std::sync::atomic::AtomicUsize,
     pub(crate) customer_by_id_count: std::sync::atomic::AtomicUsize,
     pub(crate) customer_by_id_us: std::sync::atomic::AtomicUsize,
     pub(crate) customer_by_name_count: std::sync::atomic::AtomicUsize,
     pub(crate) customer_by_name_us: std::sync::atomic::AtomicUsize,
+    pub(crate) retry_count: std::sync::atomic::AtomicUsize,
+    pub(crate) retry_exhausted_count: std::sync::atomic::AtomicUsize,
 }
 
 impl Statistics {
@@ -70,18 +84,23 @@ impl Statistics {
         if_types::Statistics {
             new_order_count: self.new_order_count.load(Relaxed) as i64,
             new_order_secs: 0.000001 * self.new_order_us.load(Relaxed) as f64,
+            new_order_rollback_count: self.new_order_rollback_count.load(Relaxed) as i64,
+            new_order_rollback_secs: 0.000001 * self.new_order_rollback_us.load(Relaxed) as f64,
             payment_count: self.payment_count.load(Relaxed) as i64,
             payment_secs: 0.000001 * self.payment_us.load(Relaxed) as f64,
             order_status_count: self.order_status_count.load(Relaxed) as i64,
             order_status_secs: 0.000001 * self.order_status_us.load(Relaxed) as f64,
             delivery_count: self.delivery_count.load(Relaxed) as i64,
             delivery_secs: 0.000001 * self.delivery_us.load(Relaxed) as f64,
+            delivery_queue_secs: 0.000001 * self.delivery_queue_us.load(Relaxed) as f64,
             stock_level_count: self.stock_level_count.load(Relaxed) as i64,
             stock_level_secs: 0.000001 * self.stock_level_us.load(Relaxed) as f64,
             customer_by_id_count: self.customer_by_id_count.load(Relaxed) as i64,
             customer_by_id_secs: 0.000001 * self.customer_by_id_us.load(Relaxed) as f64,
             customer_by_name_count: self.customer_by_name_count.load(Relaxed) as i64,
             customer_by_name_secs: 0.000001 * self.customer_by_name_us.load(Relaxed) as f64,
+            retry_count: self.retry_count.load(Relaxed) as i64,
+            retry_exhausted_count: self.retry_exhausted_count.load(Relaxed) as i64,
         }
     }
 }
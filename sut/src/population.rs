@@ -0,0 +1,69 @@
+use crate::SpawnTransaction;
+use axum::extract;
+use if_types::PopulationResponse;
+
+/// Validate initial population
+/// TPC-C standard spec. 4.3.3.1
+pub(crate) async fn check_population(
+    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+) -> Result<axum::response::Json<PopulationResponse>, crate::Error> {
+    let perflog = crate::PerformanceLog::new();
+    let ((rules, shares), mut perflog) = state
+        .spawn_read_transaction(perflog, move |conn, perflog| {
+            perflog.begin();
+
+            let result = tpcc_models::population::check(conn)?;
+
+            perflog.finish();
+            Ok::<_, crate::Error>(result)
+        })
+        .await?;
+
+    perflog.commit();
+    let perf = perflog.to_performance_metric();
+    log::debug!(
+        "check_population() : Begin {:.03}s, Query {:.03}s, Commit {:03}s",
+        perf.begin,
+        perf.query,
+        perf.commit
+    );
+
+    let valid =
+        rules.iter().all(|rule| rule.is_valid()) && shares.iter().all(|share| share.is_valid());
+    for rule in rules.iter().filter(|r| !r.is_valid()) {
+        log::warn!(
+            "Population rule \"{}\" is violated in {} of {} rows",
+            rule.description,
+            rule.violations,
+            rule.checked
+        );
+    }
+
+    let rules = rules
+        .into_iter()
+        .map(|rule| if_types::PopulationRule {
+            description: rule.description.to_string(),
+            checked: rule.checked,
+            violations: rule.violations,
+            samples: rule.samples,
+        })
+        .collect();
+    let shares = shares
+        .into_iter()
+        .map(|share| if_types::PopulationShare {
+            description: share.description.to_string(),
+            rows: share.rows,
+            matched: share.matched,
+            share: share.share(),
+            expected: share.expected,
+            valid: share.is_valid(),
+        })
+        .collect();
+
+    Ok(axum::Json(PopulationResponse {
+        valid,
+        rules,
+        shares,
+        perf,
+    }))
+}
@@ -1,61 +1,240 @@
 use crate::SpawnTransaction;
 use axum::extract;
-use if_types::{DbStatusResponse, PrepareDbRequest};
+use if_types::{
+    DbStatusParams, DbStatusResponse, PrepareDbRequest, PrepareDbResponse, TableStatus,
+};
+use if_types::{PrepareDbStatusResponse, PreparePhase};
 
+/// Database status, per table rows and sizes only if requested by `tables=true`
 pub(crate) async fn status(
     extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+    extract::Query(params): extract::Query<DbStatusParams>,
 ) -> Result<axum::response::Json<DbStatusResponse>, crate::Error> {
     let statistics = state.statistics.to_iftype();
-    state
-        .pool
-        .spawn_read_transaction(move |conn| {
-            let stat = DbStatusResponse {
-                warehouse_count: tpcc_models::Warehouse::count(conn)?,
-                district_count: tpcc_models::District::count(conn)?,
-                customer_count: tpcc_models::Customer::count(conn)?,
-                order_count: tpcc_models::Order::count(conn)?,
-                database_bytes: tpcc_models::database_size(conn)?,
-                statistics,
-            };
-
-            Ok::<_, crate::Error>(axum::response::Json(stat))
+    let (stat, _) = state
+        .spawn_read_transaction(crate::PerformanceLog::new(), move |conn, _| {
+            db_status(conn, &statistics, params.tables)
         })
-        .await
+        .await?;
+
+    Ok(axum::response::Json(stat))
 }
 
-/// Setup initial database
+/// Counts and settings of database, with rows and sizes of each table if `tables`
+fn db_status(
+    conn: &mut tpcc_models::RdConnection,
+    statistics: &if_types::Statistics,
+    tables: bool,
+) -> Result<DbStatusResponse, crate::Error> {
+    let (isolation_level, read_only) = tpcc_models::transaction_mode(conn)?;
+    Ok(DbStatusResponse {
+        warehouse_count: tpcc_models::Warehouse::count(conn)?,
+        district_count: tpcc_models::District::count(conn)?,
+        customer_count: tpcc_models::Customer::count(conn)?,
+        order_count: tpcc_models::Order::count(conn)?,
+        database_bytes: tpcc_models::database_size(conn)?,
+        isolation_level,
+        read_only,
+        database_settings: tpcc_models::database_settings(conn)?.into_iter().collect(),
+        tables: if tables { table_status(conn)? } else { vec![] },
+        nurand_constants: nurand_constants(conn)?,
+        statistics: statistics.clone(),
+    })
+}
+
+/// Rows and sizes of each table
+fn table_status(conn: &mut tpcc_models::RdConnection) -> Result<Vec<TableStatus>, crate::Error> {
+    let tables = tpcc_models::table_sizes(conn)?
+        .into_iter()
+        .map(|t| TableStatus {
+            name: t.name,
+            rows: t.rows,
+            bytes: t.bytes,
+            index_bytes: t.index_bytes,
+        })
+        .collect();
+    Ok(tables)
+}
+
+/// C_LOAD of NURand stored in initial population
+fn nurand_constants(
+    conn: &mut tpcc_models::RdConnection,
+) -> Result<Option<if_types::NURandConstants>, crate::Error> {
+    let constants = tpcc_models::nurand_constants(conn)?.map(|c| if_types::NURandConstants {
+        c_last: c.c_last,
+        c_id: c.c_id,
+        ol_i_id: c.ol_i_id,
+    });
+    Ok(constants)
+}
+
+/// Preparation jobs, one job runs at a time
+pub(crate) struct PrepareJobs {
+    jobs: std::sync::Mutex<std::collections::BTreeMap<u64, PrepareJob>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+/// Preparation job and its progress shared with the loader
+struct PrepareJob {
+    status: PrepareDbStatusResponse,
+    progress: std::sync::Arc<tpcc_models::LoadProgress>,
+}
+
+impl PrepareJobs {
+    pub fn new() -> Self {
+        Self {
+            jobs: std::sync::Mutex::new(std::collections::BTreeMap::new()),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+        }
+    }
+
+    /// Register new job, fails if another job is running
+    fn start(
+        &self,
+        params: &PrepareDbRequest,
+    ) -> Result<(u64, std::sync::Arc<tpcc_models::LoadProgress>), crate::Error> {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some((job_id, _)) = jobs.iter().find(|(_, job)| !job.status.phase.is_finished()) {
+            return Err(crate::Error::PrepareJobRunning(*job_id));
+        }
+
+        let job_id = self.next_id.fetch_add(1, Relaxed);
+        let progress = std::sync::Arc::new(tpcc_models::LoadProgress::new());
+        let status = PrepareDbStatusResponse {
+            job_id,
+            scale_factor: params.scale_factor,
+            seed: params.seed,
+            phase: PreparePhase::Queued,
+            warehouses_done: 0,
+            rows: 0,
+            started_at: chrono::Utc::now(),
+            completed_at: None,
+            error: None,
+            db_status: None,
+        };
+        jobs.insert(
+            job_id,
+            PrepareJob {
+                status,
+                progress: progress.clone(),
+            },
+        );
+
+        Ok((job_id, progress))
+    }
+
+    /// Update job status
+    fn update<F: FnOnce(&mut PrepareDbStatusResponse)>(&self, job_id: u64, f: F) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&job_id) {
+            f(&mut job.status)
+        }
+    }
+
+    /// Job status with current progress of the loader
+    fn status(&self, job_id: u64) -> Option<PrepareDbStatusResponse> {
+        let jobs = self.jobs.lock().unwrap();
+        let job = jobs.get(&job_id)?;
+        let mut status = job.status.clone();
+        status.warehouses_done = job.progress.warehouses();
+        status.rows = job.progress.rows();
+        Some(status)
+    }
+}
+
+/// Start preparation job of initial database, returns job id immediately
 pub(crate) async fn prepare_db(
     extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
     extract::Json(params): extract::Json<PrepareDbRequest>,
-) -> Result<axum::response::Json<DbStatusResponse>, crate::Error> {
-    use tpcc_models::RwTransaction;
+) -> Result<axum::response::Json<PrepareDbResponse>, crate::Error> {
+    let (job_id, progress) = state.prepare_jobs.start(&params)?;
+    log::warn!(
+        "prepare_db job {} started, scale_factor={}, seed={:?}",
+        job_id,
+        params.scale_factor,
+        params.seed
+    );
 
+    let job_state = state.clone();
     tokio::task::spawn_blocking(move || {
+        let result = run_prepare_job(&job_state, job_id, &params, &progress);
+        job_state.prepare_jobs.update(job_id, |status| {
+            status.completed_at = Some(chrono::Utc::now());
+            match result {
+                Ok(db_status) => {
+                    status.phase = PreparePhase::Completed;
+                    status.db_status = Some(db_status);
+                }
+                Err(e) => {
+                    log::error!("prepare_db job {} failed : {:?}", job_id, e);
+                    status.phase = PreparePhase::Failed;
+                    status.error = Some(format!("{:?}", e));
+                }
+            }
+        });
+    });
+
+    Ok(axum::response::Json(PrepareDbResponse { job_id }))
+}
+
+/// Progress of preparation job
+pub(crate) async fn prepare_db_status(
+    extract::State(state): extract::State<std::sync::Arc<super::AppState>>,
+    extract::Path(job_id): extract::Path<u64>,
+) -> Result<axum::response::Json<PrepareDbStatusResponse>, crate::Error> {
+    let status = state
+        .prepare_jobs
+        .status(job_id)
+        .ok_or(crate::Error::PrepareJobNotFound(job_id))?;
+
+    Ok(axum::response::Json(status))
+}
+
+/// Setup initial database
+fn run_prepare_job(
+    state: &std::sync::Arc<super::AppState>,
+    job_id: u64,
+    params: &PrepareDbRequest,
+    progress: &tpcc_models::LoadProgress,
+) -> Result<DbStatusResponse, crate::Error> {
+    use tpcc_models::RwTransaction;
+
+    let set_phase = |phase| {
+        state
+            .prepare_jobs
+            .update(job_id, |status| status.phase = phase)
+    };
+
+    {
+        set_phase(PreparePhase::Schema);
         let mut conn = state.pool.get()?;
-        conn.write_transaction(|conn| -> Result<(), crate::Error> {
-            // Clean up database
-            tpcc_models::cleanup(conn).map_err(|e| crate::Error::migration_error(e))?;
-            // Setup schema (create table) and initial data
-            tpcc_models::prepare(params.scale_factor, conn)
-                .map_err(|e| crate::Error::migration_error(e))?;
-            Ok(())
-        })?;
-
-        // Can not vacuum in transaction
-        tpcc_models::vacuum(&mut conn)?;
-
-        conn.read_transaction(|conn| {
-            let stat = DbStatusResponse {
-                warehouse_count: tpcc_models::Warehouse::count(conn)?,
-                district_count: tpcc_models::District::count(conn)?,
-                customer_count: tpcc_models::Customer::count(conn)?,
-                order_count: tpcc_models::Order::count(conn)?,
-                database_bytes: tpcc_models::database_size(conn)?,
-                statistics: state.statistics.to_iftype(),
-            };
-
-            Ok(axum::response::Json(stat))
-        })
-    })
-    .await?
+        // Clean up database and setup schema (create table)
+        tpcc_models::cleanup(&mut conn).map_err(crate::Error::migration_error)?;
+        tpcc_models::migrate(&mut conn).map_err(crate::Error::migration_error)?;
+    }
+
+    // Initial data, loaded in parallel by connections in pool
+    set_phase(PreparePhase::Load);
+    let workers = params
+        .workers
+        .unwrap_or(state.pool.max_size())
+        .min(state.pool.max_size()) as usize;
+    tpcc_models::load::<crate::Error>(
+        params.scale_factor,
+        workers,
+        params.seed,
+        params.load_time.map(|t| t.naive_utc()),
+        &state.pool,
+        progress,
+    )?;
+
+    // Can not vacuum in transaction
+    set_phase(PreparePhase::Vacuum);
+    let mut conn = state.pool.get()?;
+    tpcc_models::vacuum(&mut conn)?;
+
+    // Rows and sizes of tables are reported once after load
+    let statistics = state.statistics.to_iftype();
+    conn.read_transaction(|conn| db_status(conn, &statistics, true))
 }
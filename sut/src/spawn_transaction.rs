@@ -1,59 +1,261 @@
+use crate::PerformanceLog;
 use tpcc_models::RwTransaction;
 
 /// Run transaction in dedicated thread
+///
+/// Transaction failed by contention is retried by RetryPolicy,
+/// so `f` may be called more than once.
 pub(crate) trait SpawnTransaction {
     /// Spawn thread and run read transaction
-    async fn spawn_read_transaction<T, E, F>(&self, f: F) -> Result<T, crate::Error>
+    async fn spawn_read_transaction<T, E, F>(
+        &self,
+        perflog: PerformanceLog,
+        f: F,
+    ) -> Result<(T, PerformanceLog), crate::Error>
     where
         T: Send + 'static,
-        F: for<'a> FnOnce(&'a mut tpcc_models::RdConnection<'a>) -> Result<T, E> + Send + 'static,
+        F: for<'a> FnMut(
+                &'a mut tpcc_models::RdConnection<'a>,
+                &mut PerformanceLog,
+            ) -> Result<T, E>
+            + Send
+            + 'static,
         E: From<tpcc_models::QueryError> + Send,
         crate::Error: From<E>;
 
     /// Spawn thread and run write transaction
-    async fn spawn_write_transaction<T, E, F>(&self, f: F) -> Result<T, crate::Error>
+    async fn spawn_write_transaction<T, E, F>(
+        &self,
+        perflog: PerformanceLog,
+        f: F,
+    ) -> Result<(T, PerformanceLog), crate::Error>
     where
         T: Send + 'static,
-        F: for<'a> FnOnce(&'a mut tpcc_models::WrConnection<'a>) -> Result<T, E> + Send + 'static,
+        F: for<'a> FnMut(
+                &'a mut tpcc_models::WrConnection<'a>,
+                &mut PerformanceLog,
+            ) -> Result<T, E>
+            + Send
+            + 'static,
         E: From<tpcc_models::QueryError> + Send,
         crate::Error: From<E>;
 }
 
-impl SpawnTransaction for tpcc_models::Pool {
+impl SpawnTransaction for std::sync::Arc<crate::AppState> {
     /// Run read transaction in
-    async fn spawn_read_transaction<T, E, F>(&self, f: F) -> Result<T, crate::Error>
+    async fn spawn_read_transaction<T, E, F>(
+        &self,
+        perflog: PerformanceLog,
+        mut f: F,
+    ) -> Result<(T, PerformanceLog), crate::Error>
     where
         T: Send + 'static,
-        F: for<'a> FnOnce(&'a mut tpcc_models::RdConnection<'a>) -> Result<T, E> + Send + 'static,
+        F: for<'a> FnMut(
+                &'a mut tpcc_models::RdConnection<'a>,
+                &mut PerformanceLog,
+            ) -> Result<T, E>
+            + Send
+            + 'static,
         E: From<tpcc_models::QueryError> + Send,
         crate::Error: From<E>,
     {
-        let pool = self.clone();
-        let result = tokio::task::spawn_blocking(move || -> Result<T, crate::Error> {
-            let mut conn = pool.get()?;
-            let t = conn.read_transaction(f)?;
-            Ok(t)
+        let state = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state.pool.get()?;
+            state
+                .retry_policy
+                .run(&state.statistics, perflog, |perflog| {
+                    let t = conn.read_transaction(|conn| f(conn, perflog))?;
+                    Ok(t)
+                })
         })
         .await?;
 
         result
     }
 
-    async fn spawn_write_transaction<T, E, F>(&self, f: F) -> Result<T, crate::Error>
+    async fn spawn_write_transaction<T, E, F>(
+        &self,
+        perflog: PerformanceLog,
+        mut f: F,
+    ) -> Result<(T, PerformanceLog), crate::Error>
     where
         T: Send + 'static,
-        F: for<'a> FnOnce(&'a mut tpcc_models::WrConnection<'a>) -> Result<T, E> + Send + 'static,
+        F: for<'a> FnMut(
+                &'a mut tpcc_models::WrConnection<'a>,
+                &mut PerformanceLog,
+            ) -> Result<T, E>
+            + Send
+            + 'static,
         E: From<tpcc_models::QueryError> + Send,
         crate::Error: From<E>,
     {
-        let pool = self.clone();
-        let result = tokio::task::spawn_blocking(move || -> Result<T, crate::Error> {
-            let mut conn = pool.get()?;
-            let t = conn.write_transaction(f)?;
-            Ok(t)
+        let state = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = state.pool.get()?;
+            state
+                .retry_policy
+                .run(&state.statistics, perflog, |perflog| {
+                    let t = conn.write_transaction(|conn| f(conn, perflog))?;
+                    Ok(t)
+                })
         })
         .await?;
 
         result
     }
 }
+
+/// Retry policy for transactions failed by contention
+///
+/// Configured by environment variables
+///   TX_MAX_ATTEMPTS : attempts including the first one (default 3, 1 for no retry)
+///   TX_RETRY_BACKOFF_MS : wait before the first retry, doubled for each retry (default 10)
+///   TX_RETRYABLE_ERRORS : comma separated RetryableError (default "serialization_failure,deadlock,busy")
+#[derive(Debug)]
+pub(crate) struct RetryPolicy {
+    max_attempts: u32,
+    backoff: std::time::Duration,
+    retryable: Vec<RetryableError>,
+}
+
+/// Database errors to be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RetryableError {
+    /// PostgreSQL serialization failure (SQLSTATE 40001)
+    SerializationFailure,
+    /// PostgreSQL deadlock (SQLSTATE 40P01), MySQL deadlock (ER_LOCK_DEADLOCK)
+    Deadlock,
+    /// SQLite SQLITE_BUSY after busy_timeout, MySQL lock wait timeout (ER_LOCK_WAIT_TIMEOUT)
+    Busy,
+    /// Unique constraint violation by concurrent insert
+    UniqueViolation,
+}
+
+impl std::str::FromStr for RetryableError {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "serialization_failure" => Ok(Self::SerializationFailure),
+            "deadlock" => Ok(Self::Deadlock),
+            "busy" => Ok(Self::Busy),
+            "unique_violation" => Ok(Self::UniqueViolation),
+            _ => Err(format!("unknown retryable error {}", s)),
+        }
+    }
+}
+
+impl RetryableError {
+    /// Classify database error by the error kind of diesel.
+    ///
+    /// Diesel has no kind for deadlock and lock timeout, these are classified by error message
+    /// as fallback for each backend:
+    ///   PostgreSQL : "deadlock detected" (40P01), reported as Unknown
+    ///   MySQL : "Deadlock found" (ER_LOCK_DEADLOCK), reported as SerializationFailure,
+    ///           "Lock wait timeout exceeded" (ER_LOCK_WAIT_TIMEOUT), reported as Unknown
+    ///   SQLite : "database is locked", "database is busy" (SQLITE_BUSY), reported as Unknown
+    fn of_error(kind: &tpcc_models::DatabaseErrorKind, message: &str) -> Option<Self> {
+        use tpcc_models::DatabaseErrorKind;
+        match kind {
+            DatabaseErrorKind::SerializationFailure if message.contains("Deadlock found") => {
+                Some(Self::Deadlock)
+            }
+            DatabaseErrorKind::SerializationFailure => Some(Self::SerializationFailure),
+            DatabaseErrorKind::UniqueViolation => Some(Self::UniqueViolation),
+            DatabaseErrorKind::Unknown if message.contains("deadlock detected") => {
+                Some(Self::Deadlock)
+            }
+            DatabaseErrorKind::Unknown
+                if message.contains("database is locked")
+                    || message.contains("database is busy")
+                    || message.contains("Lock wait timeout exceeded") =>
+            {
+                Some(Self::Busy)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let max_attempts = if let Ok(attempts) = std::env::var("TX_MAX_ATTEMPTS") {
+            attempts
+                .parse::<u32>()
+                .expect("Can not parse TX_MAX_ATTEMPTS as integer")
+                .max(1)
+        } else {
+            3
+        };
+        let backoff_ms = if let Ok(backoff) = std::env::var("TX_RETRY_BACKOFF_MS") {
+            backoff
+                .parse::<u64>()
+                .expect("Can not parse TX_RETRY_BACKOFF_MS as integer")
+        } else {
+            10
+        };
+        let retryable = std::env::var("TX_RETRYABLE_ERRORS")
+            .unwrap_or("serialization_failure,deadlock,busy".to_string())
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.parse::<RetryableError>())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Can not parse TX_RETRYABLE_ERRORS");
+
+        Self {
+            max_attempts,
+            backoff: std::time::Duration::from_millis(backoff_ms),
+            retryable,
+        }
+    }
+
+    fn is_retryable(&self, e: &crate::Error) -> bool {
+        use tpcc_models::QueryError;
+        match e {
+            crate::Error::DbQueryError(QueryError::DatabaseError(kind, info)) => {
+                RetryableError::of_error(kind, info.message())
+                    .is_some_and(|error| self.retryable.contains(&error))
+            }
+            _ => false,
+        }
+    }
+
+    /// Run `f` until it succeeds, fails by non-retryable error, or reaches max attempts.
+    /// Each attempt starts with a copy of `perflog`, so its begin time includes failed attempts.
+    fn run<T, F>(
+        &self,
+        statistics: &crate::perf::Statistics,
+        perflog: PerformanceLog,
+        mut f: F,
+    ) -> Result<(T, PerformanceLog), crate::Error>
+    where
+        F: FnMut(&mut PerformanceLog) -> Result<T, crate::Error>,
+    {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let mut retries = 0;
+        loop {
+            let mut attempt_log = perflog.clone();
+            attempt_log.set_retries(retries);
+            match f(&mut attempt_log) {
+                Ok(t) => return Ok((t, attempt_log)),
+                Err(e) if self.is_retryable(&e) => {
+                    if self.max_attempts <= retries + 1 {
+                        log::warn!(
+                            "Transaction failed after {} attempts : {:?}",
+                            retries + 1,
+                            e
+                        );
+                        statistics.retry_exhausted_count.fetch_add(1, Relaxed);
+                        return Err(e);
+                    }
+                    log::debug!("Retry transaction : {:?}", e);
+                    statistics.retry_count.fetch_add(1, Relaxed);
+                    std::thread::sleep(self.backoff * 2u32.saturating_pow(retries));
+                    retries += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -13,9 +13,7 @@ pub(crate) async fn check_stocks(
 
     let perflog = crate::PerformanceLog::new();
     let (contents, mut perflog) = state
-        .pool
-        .spawn_read_transaction(move |conn| {
-            let mut perflog = perflog;
+        .spawn_read_transaction(perflog, move |conn, perflog| {
             perflog.begin();
 
             let warehouse = tpcc_models::Warehouse::find(warehouse_id, conn)?;
@@ -24,12 +22,9 @@ pub(crate) async fn check_stocks(
             let low_stocks = district.check_stock_level(params.stock_level, conn)?;
 
             perflog.finish();
-            Ok::<_, crate::Error>((
-                if_types::StockLevelContents {
-                    low_stocks: low_stocks as i32,
-                },
-                perflog,
-            ))
+            Ok::<_, crate::Error>(if_types::StockLevelContents {
+                low_stocks: low_stocks as i32,
+            })
         })
         .await?;
 
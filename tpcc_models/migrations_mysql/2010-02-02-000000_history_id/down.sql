@@ -0,0 +1 @@
+ALTER TABLE histories MODIFY h_id INTEGER NOT NULL;
@@ -0,0 +1,2 @@
+-- h_id is assigned by AUTO_INCREMENT column
+ALTER TABLE histories MODIFY h_id INTEGER NOT NULL AUTO_INCREMENT;
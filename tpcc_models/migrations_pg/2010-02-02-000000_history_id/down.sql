@@ -0,0 +1 @@
+ALTER TABLE histories ALTER COLUMN h_id DROP IDENTITY;
@@ -0,0 +1,3 @@
+-- h_id is assigned by IDENTITY column
+ALTER TABLE histories ALTER COLUMN h_id ADD GENERATED BY DEFAULT AS IDENTITY;
+SELECT setval(pg_get_serial_sequence('histories', 'h_id'), COALESCE(max(h_id), 0) + 1, false) FROM histories;
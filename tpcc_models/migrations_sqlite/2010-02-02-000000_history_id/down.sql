@@ -0,0 +1,18 @@
+CREATE TABLE histories_old (
+  h_id     INTEGER          NOT NULL, -- h_id is not required in TPC-C
+  h_c_id   INTEGER          NOT NULL,
+  h_c_d_id INTEGER          NOT NULL,
+  h_c_w_id INTEGER          NOT NULL,
+  h_d_id   INTEGER          NOT NULL,
+  h_w_id   INTEGER          NOT NULL,
+  h_date   TIMESTAMP        NOT NULL,
+  h_amount DOUBLE PRECISION NOT NULL,
+  h_data   TEXT             NOT NULL,
+  PRIMARY KEY (h_id),
+  FOREIGN KEY (h_c_w_id, h_c_d_id, h_c_id) REFERENCES customers (c_w_id, c_d_id, c_id),
+  FOREIGN KEY (h_w_id, h_d_id)             REFERENCES districts (d_w_id, d_id)
+)WITHOUT ROWID;
+
+INSERT INTO histories_old SELECT * FROM histories;
+DROP TABLE histories;
+ALTER TABLE histories_old RENAME TO histories;
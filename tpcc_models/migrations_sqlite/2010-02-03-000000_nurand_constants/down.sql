@@ -0,0 +1 @@
+DROP TABLE nurand_constants;
@@ -0,0 +1,6 @@
+-- Constant C of NURand(A, x, y) used in initial population, TPC-C standard spec. 2.1.6
+CREATE TABLE nurand_constants (
+  nc_a INTEGER NOT NULL,
+  nc_c INTEGER NOT NULL,
+  PRIMARY KEY (nc_a)
+)WITHOUT ROWID;
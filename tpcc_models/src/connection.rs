@@ -0,0 +1,436 @@
+use diesel::prelude::*;
+
+/// Database connection, backend is chosen by scheme of database URL
+///   `postgres://...`, `postgresql://...` : PostgreSQL
+///   `mysql://...` : MySQL, MariaDB
+///   `sqlite://path`, `sqlite:path`, or file path : SQLite
+#[derive(diesel::MultiConnection)]
+pub enum DbConnection {
+    #[cfg(feature = "sqlite")]
+    Sqlite(diesel::sqlite::SqliteConnection),
+    #[cfg(feature = "postgres")]
+    Postgres(diesel::pg::PgConnection),
+    #[cfg(feature = "mysql")]
+    Mysql(diesel::mysql::MysqlConnection),
+}
+
+pub type Pool = diesel::r2d2::Pool<ConnectionManager>;
+
+/// Run `$body` with the backend connection `$backend_conn` in DbConnection
+///
+/// For queries MultiConnection can not run,
+/// e.g. batch INSERT in SQLite, and RETURNING with Selectable.
+macro_rules! with_backend {
+    ($conn:expr, |$backend_conn:ident| $body:expr) => {
+        match $conn {
+            #[cfg(feature = "sqlite")]
+            $crate::DbConnection::Sqlite($backend_conn) => $body,
+            #[cfg(feature = "postgres")]
+            $crate::DbConnection::Postgres($backend_conn) => $body,
+            #[cfg(feature = "mysql")]
+            $crate::DbConnection::Mysql($backend_conn) => $body,
+        }
+    };
+}
+pub(crate) use with_backend;
+
+/// `diesel::update($target).set($changes)` and load updated rows by `$load` (get_result, get_results)
+///
+/// MySQL has no RETURNING clause,
+/// so UPDATE and SELECT `$target` again by `$select` (first, load) in the same transaction.
+macro_rules! update_returning {
+    ($conn:expr, $target:expr, $changes:expr, $load:ident, $select:ident) => {
+        match $conn {
+            #[cfg(feature = "sqlite")]
+            $crate::DbConnection::Sqlite(db) => diesel::update($target).set($changes).$load(db),
+            #[cfg(feature = "postgres")]
+            $crate::DbConnection::Postgres(db) => diesel::update($target).set($changes).$load(db),
+            #[cfg(feature = "mysql")]
+            $crate::DbConnection::Mysql(db) => {
+                diesel::update($target.clone()).set($changes).execute(db)?;
+                $target.$select(db)
+            }
+        }
+    };
+}
+pub(crate) use update_returning;
+
+/// Rows in one INSERT statement of bulk_insert!,
+/// within limit of bind parameters (32766 in SQLite, 65535 in MySQL)
+pub(crate) const BULK_INSERT_ROWS: usize = 1_000;
+
+/// Insert all `$rows` into `$table` and return number of rows
+///   PostgreSQL : COPY FROM in binary format
+///   SQLite, MySQL : multi-row INSERT, split into BULK_INSERT_ROWS rows
+macro_rules! bulk_insert {
+    ($conn:expr, $table:expr, $rows:expr) => {
+        match $conn {
+            #[cfg(feature = "sqlite")]
+            $crate::DbConnection::Sqlite(db) => $rows
+                .chunks($crate::connection::BULK_INSERT_ROWS)
+                .map(|rows| diesel::insert_into($table).values(rows).execute(db))
+                .sum::<QueryResult<usize>>(),
+            #[cfg(feature = "postgres")]
+            $crate::DbConnection::Postgres(db) => {
+                diesel::copy_from($table).from_insertable($rows).execute(db)
+            }
+            #[cfg(feature = "mysql")]
+            $crate::DbConnection::Mysql(db) => $rows
+                .chunks($crate::connection::BULK_INSERT_ROWS)
+                .map(|rows| diesel::insert_into($table).values(rows).execute(db))
+                .sum::<QueryResult<usize>>(),
+        }
+    };
+}
+pub(crate) use bulk_insert;
+
+/// Database backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+impl Backend {
+    /// Backend and URL passed to the backend
+    fn from_url(db_url: &str) -> (Self, &str) {
+        if db_url.starts_with("postgres://") || db_url.starts_with("postgresql://") {
+            (Self::Postgres, db_url)
+        } else if db_url.starts_with("mysql://") {
+            (Self::Mysql, db_url)
+        } else if let Some(path) = db_url.strip_prefix("sqlite://") {
+            (Self::Sqlite, path)
+        } else if let Some(path) = db_url.strip_prefix("sqlite:") {
+            (Self::Sqlite, path)
+        } else {
+            (Self::Sqlite, db_url)
+        }
+    }
+}
+
+/// Connect to database without session setup
+fn establish(db_url: &str) -> ConnectionResult<DbConnection> {
+    match Backend::from_url(db_url) {
+        #[cfg(feature = "sqlite")]
+        (Backend::Sqlite, path) => crate::sqlite::establish(path).map(DbConnection::Sqlite),
+        #[cfg(feature = "postgres")]
+        (Backend::Postgres, url) => crate::pg::establish(url).map(DbConnection::Postgres),
+        #[cfg(feature = "mysql")]
+        (Backend::Mysql, url) => crate::mysql::establish(url).map(DbConnection::Mysql),
+        #[allow(unreachable_patterns)]
+        (backend, _) => Err(ConnectionError::BadConnection(format!(
+            "{:?} backend is not enabled in this build",
+            backend
+        ))),
+    }
+}
+
+/// Session setup in initial connection
+#[allow(unused_variables)]
+fn setup_conn(
+    conn: &mut DbConnection,
+    options: &crate::TransactionOptions,
+    sqlite_options: &crate::SqliteOptions,
+) -> QueryResult<()> {
+    match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::setup_conn(conn, sqlite_options),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::setup_conn(conn, options),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::setup_conn(conn, options),
+    }
+}
+
+/// Connect to database
+pub fn connect(db_url: &str) -> ConnectionResult<DbConnection> {
+    let mut conn = establish(db_url)?;
+    setup_conn(
+        &mut conn,
+        &crate::TransactionOptions::default(),
+        &crate::SqliteOptions::default(),
+    )
+    .map_err(ConnectionError::CouldntSetupConfiguration)?;
+    Ok(conn)
+}
+
+/// Make database pool
+///
+/// SQLite transactions are always SERIALIZABLE, `options` is used only for PostgreSQL and MySQL.
+/// `sqlite_options` is used only for SQLite.
+pub fn pool(
+    db_url: &str,
+    connections: u32,
+    options: &crate::TransactionOptions,
+    sqlite_options: &crate::SqliteOptions,
+) -> Result<Pool, diesel::r2d2::PoolError> {
+    let manager = ConnectionManager {
+        db_url: db_url.to_string(),
+    };
+
+    Pool::builder()
+        .max_size(connections)
+        .connection_customizer(Box::new(CustomOptions(
+            options.clone(),
+            sqlite_options.clone(),
+        )))
+        .build(manager)
+}
+
+/// r2d2 connection manager, same as diesel::r2d2::ConnectionManager
+/// except for choosing backend by database URL
+#[derive(Debug)]
+pub struct ConnectionManager {
+    db_url: String,
+}
+
+impl diesel::r2d2::ManageConnection for ConnectionManager {
+    type Connection = DbConnection;
+    type Error = diesel::r2d2::Error;
+
+    fn connect(&self) -> Result<DbConnection, Self::Error> {
+        establish(&self.db_url).map_err(diesel::r2d2::Error::ConnectionError)
+    }
+
+    fn is_valid(&self, conn: &mut DbConnection) -> Result<(), Self::Error> {
+        use diesel::r2d2::R2D2Connection;
+        conn.ping().map_err(diesel::r2d2::Error::QueryError)
+    }
+
+    fn has_broken(&self, conn: &mut DbConnection) -> bool {
+        use diesel::r2d2::R2D2Connection;
+        std::thread::panicking() || conn.is_broken()
+    }
+}
+
+/// Customize session options
+#[derive(Debug)]
+struct CustomOptions(crate::TransactionOptions, crate::SqliteOptions);
+impl diesel::r2d2::CustomizeConnection<DbConnection, diesel::r2d2::Error> for CustomOptions {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), diesel::r2d2::Error> {
+        setup_conn(conn, &self.0, &self.1).map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Run vacuum
+pub fn vacuum(conn: &mut DbConnection) -> QueryResult<()> {
+    with_read_write_session(conn, |conn| match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::vacuum(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::vacuum(conn),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::vacuum(conn),
+    })
+}
+
+/// Run `f` out of transaction in READ WRITE session, e.g. DDL of migrations and VACUUM
+///
+/// Sessions of PostgreSQL and MySQL are READ ONLY by TransactionOptions::read_only,
+/// the setting is restored after `f`. SQLite has no READ ONLY session.
+pub(crate) fn with_read_write_session<T, E, F>(conn: &mut DbConnection, f: F) -> Result<T, E>
+where
+    E: From<diesel::result::Error>,
+    F: FnOnce(&mut DbConnection) -> Result<T, E>,
+{
+    let read_only = match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(_) => false,
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::session_read_only(conn)?,
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::session_read_only(conn)?,
+    };
+    if !read_only {
+        return f(conn);
+    }
+
+    set_session_read_only(conn, false)?;
+    let result = f(conn);
+    set_session_read_only(conn, true)?;
+    result
+}
+
+/// Set default of transactions in session, only called for READ ONLY sessions
+#[allow(unused_variables)]
+fn set_session_read_only(conn: &mut DbConnection, read_only: bool) -> QueryResult<()> {
+    match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(_) => Ok(()),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::set_session_read_only(conn, read_only),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::set_session_read_only(conn, read_only),
+    }
+}
+
+/// Prepare database for bulk load by loader::load()
+///   SQLite : drop secondary indexes to build them after load
+#[allow(unused_variables)]
+pub(crate) fn before_load(conn: &mut DbConnection) -> QueryResult<()> {
+    match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::drop_secondary_indexes(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(_) => Ok(()),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(_) => Ok(()),
+    }
+}
+
+/// Finish bulk load by loader::load()
+///   SQLite : build secondary indexes dropped in before_load()
+#[allow(unused_variables)]
+pub(crate) fn after_load(conn: &mut DbConnection) -> QueryResult<()> {
+    match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::create_secondary_indexes(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(_) => Ok(()),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(_) => Ok(()),
+    }
+}
+
+/// Database allows only one write transaction at a time
+pub(crate) fn is_single_writer(conn: &DbConnection) -> bool {
+    match conn {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(_) => true,
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(_) => false,
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(_) => false,
+    }
+}
+
+/// Database size in bytes
+pub fn database_size(conn: &mut crate::RdConnection) -> QueryResult<i64> {
+    match conn.as_db() {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::database_size(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::database_size(conn),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::database_size(conn),
+    }
+}
+
+/// TPC-C tables, TPC-C standard spec. 1.3
+pub(crate) const TABLES: [&str; 9] = [
+    "warehouses",
+    "districts",
+    "customers",
+    "histories",
+    "items",
+    "stocks",
+    "orders",
+    "new_orders",
+    "order_lines",
+];
+
+/// Rows and sizes of table
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TableSize {
+    #[diesel(sql_type = diesel::sql_types::Text, column_name = "table_name")]
+    pub name: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt, column_name = "table_rows")]
+    pub rows: i64,
+    /// Table size in bytes, including indexes
+    #[diesel(sql_type = diesel::sql_types::BigInt, column_name = "table_bytes")]
+    pub bytes: i64,
+    /// Size of indexes in bytes
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub index_bytes: i64,
+}
+
+/// Rows and sizes of TPC-C tables
+pub fn table_sizes(conn: &mut crate::RdConnection) -> QueryResult<Vec<TableSize>> {
+    TABLES
+        .iter()
+        .map(|table| match conn.as_db() {
+            #[cfg(feature = "sqlite")]
+            DbConnection::Sqlite(conn) => crate::sqlite::table_size(conn, table),
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres(conn) => crate::pg::table_size(conn, table),
+            #[cfg(feature = "mysql")]
+            DbConnection::Mysql(conn) => crate::mysql::table_size(conn, table),
+        })
+        .collect()
+}
+
+/// Settings of database connection in effect, as pairs of name and value
+///
+/// Only SQLite PRAGMAs are reported, PostgreSQL and MySQL return nothing.
+pub fn database_settings(conn: &mut crate::RdConnection) -> QueryResult<Vec<(String, String)>> {
+    match conn.as_db() {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::database_settings(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(_) => Ok(vec![]),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(_) => Ok(vec![]),
+    }
+}
+
+/// Isolation level and READ ONLY of current transaction
+pub fn transaction_mode(conn: &mut crate::RdConnection) -> QueryResult<(String, bool)> {
+    match conn.as_db() {
+        #[cfg(feature = "sqlite")]
+        DbConnection::Sqlite(conn) => crate::sqlite::transaction_mode(conn),
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => crate::pg::transaction_mode(conn),
+        #[cfg(feature = "mysql")]
+        DbConnection::Mysql(conn) => crate::mysql::transaction_mode(conn),
+    }
+}
+
+impl crate::RwTransaction for DbConnection {
+    /// BEGIN TRANSACTION
+    ///   SQLite : BEGIN DEFERRED, SHARED lock will be held at first query statement (SELECT)
+    ///   PostgreSQL, MySQL : isolation level and READ ONLY are given by session defaults
+    fn read_transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        for<'b> F: FnOnce(&'b mut crate::RdConnection<'b>) -> Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        diesel::connection::Connection::transaction(self, |conn| {
+            f(&mut crate::RdConnection::new(conn))
+        })
+    }
+
+    /// BEGIN TRANSACTION for write
+    ///   SQLite : BEGIN IMMEDIATE, try taking lock for write at first
+    ///   PostgreSQL : BEGIN TRANSACTION READ WRITE
+    ///   MySQL : START TRANSACTION READ WRITE
+    fn write_transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        for<'b> F: FnOnce(&'b mut crate::WrConnection<'b>) -> Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        use diesel::connection::TransactionManager;
+        type Manager = <DbConnection as diesel::connection::Connection>::TransactionManager;
+
+        match self {
+            #[cfg(feature = "sqlite")]
+            DbConnection::Sqlite(conn) => crate::sqlite::begin_write_transaction(conn)?,
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres(conn) => crate::pg::begin_write_transaction(conn)?,
+            #[cfg(feature = "mysql")]
+            DbConnection::Mysql(conn) => crate::mysql::begin_write_transaction(conn)?,
+        }
+
+        // Same as diesel::connection::TransactionManager::transaction()
+        match f(&mut crate::WrConnection::new(self)) {
+            Ok(value) => {
+                Manager::commit_transaction(self)?;
+                Ok(value)
+            }
+            Err(user_error) => match Manager::rollback_transaction(self) {
+                Ok(()) | Err(diesel::result::Error::BrokenTransactionManager) => Err(user_error),
+                Err(rollback_error) => Err(rollback_error.into()),
+            },
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Consistency conditions
+//! TPC-C standard spec. 3.3.2
+use crate::{schema, RdConnection};
+use diesel::dsl::{count, count_star};
+use diesel::prelude::*;
+
+/// Maximum number of sample rows recorded for each condition
+const MAX_SAMPLES: usize = 10;
+
+/// Allowed difference of money amounts, from rounding error of floating point
+const AMOUNT_TOLERANCE: f64 = 0.005;
+
+/// Consistency condition with its check result
+#[derive(Debug)]
+pub struct ConsistencyCondition {
+    /// Condition number, TPC-C standard spec. 3.3.2.x
+    pub number: u32,
+    pub description: &'static str,
+    /// Only required to be maintained in initial database population
+    pub initial_only: bool,
+    /// Number of checked rows (warehouses, districts, orders or customers)
+    pub checked: i64,
+    /// Number of rows violating the condition
+    pub violations: i64,
+    /// Violating rows, up to MAX_SAMPLES
+    pub samples: Vec<String>,
+}
+
+impl ConsistencyCondition {
+    fn new(number: u32, description: &'static str) -> Self {
+        Self {
+            number,
+            description,
+            initial_only: false,
+            checked: 0,
+            violations: 0,
+            samples: vec![],
+        }
+    }
+
+    /// Record check result of single row
+    fn check<F: FnOnce() -> String>(&mut self, consistent: bool, sample: F) {
+        self.checked += 1;
+        if !consistent {
+            self.violations += 1;
+            if self.samples.len() < MAX_SAMPLES {
+                self.samples.push(sample());
+            }
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.violations == 0
+    }
+}
+
+fn amount_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() < AMOUNT_TOLERANCE
+}
+
+/// Check all consistency conditions 1 to 12
+/// TPC-C standard spec. 3.3.2
+pub fn check(conn: &mut RdConnection) -> QueryResult<Vec<ConsistencyCondition>> {
+    use schema::{districts, histories, new_orders, order_lines, orders, warehouses};
+    use std::collections::HashMap;
+
+    let mut c1 = ConsistencyCondition::new(1, "W_YTD = sum(D_YTD)");
+    let mut c2 = ConsistencyCondition::new(2, "D_NEXT_O_ID - 1 = max(O_ID) = max(NO_O_ID)");
+    let mut c3 = ConsistencyCondition::new(
+        3,
+        "max(NO_O_ID) - min(NO_O_ID) + 1 = number of rows in NEW-ORDER for each district",
+    );
+    let mut c4 = ConsistencyCondition::new(
+        4,
+        "sum(O_OL_CNT) = number of rows in ORDER-LINE for each district",
+    );
+    let mut c5 = ConsistencyCondition::new(
+        5,
+        "O_CARRIER_ID is null if and only if a corresponding row in NEW-ORDER exists",
+    );
+    let mut c6 =
+        ConsistencyCondition::new(6, "O_OL_CNT = number of rows in ORDER-LINE for each order");
+    let mut c7 = ConsistencyCondition::new(
+        7,
+        "OL_DELIVERY_D is null if and only if O_CARRIER_ID is null",
+    );
+    let mut c8 = ConsistencyCondition::new(8, "W_YTD = sum(H_AMOUNT) for each warehouse");
+    let mut c9 = ConsistencyCondition::new(9, "D_YTD = sum(H_AMOUNT) for each district");
+    let mut c10 = ConsistencyCondition::new(
+        10,
+        "C_BALANCE = sum(OL_AMOUNT of delivered orders) - sum(H_AMOUNT) for each customer",
+    );
+    let mut c11 = ConsistencyCondition::new(
+        11,
+        "count(ORDER) - count(NEW-ORDER) = 2100 for each district",
+    );
+    c11.initial_only = true;
+    let mut c12 = ConsistencyCondition::new(
+        12,
+        "C_BALANCE + C_YTD_PAYMENT = sum(OL_AMOUNT of delivered orders) for each customer",
+    );
+
+    // Warehouse level, conditions 1 and 8
+    let district_ytd = districts::table
+        .group_by(districts::d_w_id)
+        .select((districts::d_w_id, diesel::dsl::sum(districts::d_ytd)))
+        .load::<(i32, Option<f64>)>(conn.as_db())?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let warehouse_history = histories::table
+        .group_by(histories::h_w_id)
+        .select((histories::h_w_id, diesel::dsl::sum(histories::h_amount)))
+        .load::<(i32, Option<f64>)>(conn.as_db())?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let warehouses = warehouses::table
+        .select((warehouses::w_id, warehouses::w_ytd))
+        .order(warehouses::w_id)
+        .load::<(i32, f64)>(conn.as_db())?;
+    for (w_id, w_ytd) in warehouses {
+        let d_ytd = district_ytd.get(&w_id).copied().flatten().unwrap_or(0.0);
+        c1.check(amount_eq(w_ytd, d_ytd), || {
+            format!("W_ID={}: W_YTD={:.2}, sum(D_YTD)={:.2}", w_id, w_ytd, d_ytd)
+        });
+        let h_amount = warehouse_history
+            .get(&w_id)
+            .copied()
+            .flatten()
+            .unwrap_or(0.0);
+        c8.check(amount_eq(w_ytd, h_amount), || {
+            format!(
+                "W_ID={}: W_YTD={:.2}, sum(H_AMOUNT)={:.2}",
+                w_id, w_ytd, h_amount
+            )
+        });
+    }
+
+    // District level, conditions 2, 3, 4, 9 and 11
+    let order_summary = orders::table
+        .group_by((orders::o_w_id, orders::o_d_id))
+        .select((
+            orders::o_w_id,
+            orders::o_d_id,
+            diesel::dsl::max(orders::o_id),
+            diesel::dsl::sum(orders::o_ol_cnt),
+            count_star(),
+        ))
+        .load::<(i32, i32, Option<i32>, Option<i64>, i64)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, max_o_id, ol_cnt, count)| ((w_id, d_id), (max_o_id, ol_cnt, count)))
+        .collect::<HashMap<_, _>>();
+    let new_order_summary = new_orders::table
+        .group_by((new_orders::no_w_id, new_orders::no_d_id))
+        .select((
+            new_orders::no_w_id,
+            new_orders::no_d_id,
+            diesel::dsl::max(new_orders::no_o_id),
+            diesel::dsl::min(new_orders::no_o_id),
+            count_star(),
+        ))
+        .load::<(i32, i32, Option<i32>, Option<i32>, i64)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, max_id, min_id, count)| ((w_id, d_id), (max_id, min_id, count)))
+        .collect::<HashMap<_, _>>();
+    let order_line_counts = order_lines::table
+        .group_by((order_lines::ol_w_id, order_lines::ol_d_id))
+        .select((order_lines::ol_w_id, order_lines::ol_d_id, count_star()))
+        .load::<(i32, i32, i64)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, count)| ((w_id, d_id), count))
+        .collect::<HashMap<_, _>>();
+    let district_history = histories::table
+        .group_by((histories::h_w_id, histories::h_d_id))
+        .select((
+            histories::h_w_id,
+            histories::h_d_id,
+            diesel::dsl::sum(histories::h_amount),
+        ))
+        .load::<(i32, i32, Option<f64>)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, amount)| ((w_id, d_id), amount))
+        .collect::<HashMap<_, _>>();
+
+    let districts = districts::table
+        .select((
+            districts::d_w_id,
+            districts::d_id,
+            districts::d_ytd,
+            districts::d_next_o_id,
+        ))
+        .order((districts::d_w_id, districts::d_id))
+        .load::<(i32, i32, f64, i32)>(conn.as_db())?;
+    for &(w_id, d_id, d_ytd, d_next_o_id) in &districts {
+        let (max_o_id, ol_cnt, order_count) = order_summary
+            .get(&(w_id, d_id))
+            .copied()
+            .unwrap_or((None, None, 0));
+        let (max_no_o_id, min_no_o_id, new_order_count) = new_order_summary
+            .get(&(w_id, d_id))
+            .copied()
+            .unwrap_or((None, None, 0));
+
+        // max(NO_O_ID) is not defined when all orders are delivered
+        let max_o_id = max_o_id.unwrap_or(0);
+        c2.check(
+            d_next_o_id - 1 == max_o_id && max_no_o_id.is_none_or(|no| no == max_o_id),
+            || {
+                format!(
+                    "W_ID={}, D_ID={}: D_NEXT_O_ID={}, max(O_ID)={}, max(NO_O_ID)={:?}",
+                    w_id, d_id, d_next_o_id, max_o_id, max_no_o_id
+                )
+            },
+        );
+
+        let new_order_range = match (max_no_o_id, min_no_o_id) {
+            (Some(max_id), Some(min_id)) => (max_id - min_id + 1) as i64,
+            _ => 0,
+        };
+        c3.check(new_order_range == new_order_count, || {
+            format!(
+                "W_ID={}, D_ID={}: max(NO_O_ID)={:?}, min(NO_O_ID)={:?}, count(NEW-ORDER)={}",
+                w_id, d_id, max_no_o_id, min_no_o_id, new_order_count
+            )
+        });
+
+        let ol_cnt = ol_cnt.unwrap_or(0);
+        let order_line_count = order_line_counts.get(&(w_id, d_id)).copied().unwrap_or(0);
+        c4.check(ol_cnt == order_line_count, || {
+            format!(
+                "W_ID={}, D_ID={}: sum(O_OL_CNT)={}, count(ORDER-LINE)={}",
+                w_id, d_id, ol_cnt, order_line_count
+            )
+        });
+
+        let h_amount = district_history
+            .get(&(w_id, d_id))
+            .copied()
+            .flatten()
+            .unwrap_or(0.0);
+        c9.check(amount_eq(d_ytd, h_amount), || {
+            format!(
+                "W_ID={}, D_ID={}: D_YTD={:.2}, sum(H_AMOUNT)={:.2}",
+                w_id, d_id, d_ytd, h_amount
+            )
+        });
+
+        c11.check(order_count - new_order_count == 2100, || {
+            format!(
+                "W_ID={}, D_ID={}: count(ORDER)={}, count(NEW-ORDER)={}",
+                w_id, d_id, order_count, new_order_count
+            )
+        });
+    }
+
+    // Order and customer level, conditions 5, 6, 7, 10 and 12
+    for &(w_id, d_id, _, _) in &districts {
+        check_district_orders(
+            w_id,
+            d_id,
+            [&mut c5, &mut c6, &mut c7, &mut c10, &mut c12],
+            conn,
+        )?;
+    }
+
+    Ok(vec![c1, c2, c3, c4, c5, c6, c7, c8, c9, c10, c11, c12])
+}
+
+/// Check conditions 5, 6, 7, 10 and 12 for orders and customers in single district
+fn check_district_orders(
+    w_id: i32,
+    d_id: i32,
+    [c5, c6, c7, c10, c12]: [&mut ConsistencyCondition; 5],
+    conn: &mut RdConnection,
+) -> QueryResult<()> {
+    use schema::{customers, histories, new_orders, order_lines, orders};
+    use std::collections::{HashMap, HashSet};
+
+    let new_order_ids = new_orders::table
+        .filter(new_orders::no_w_id.eq(w_id))
+        .filter(new_orders::no_d_id.eq(d_id))
+        .select(new_orders::no_o_id)
+        .load::<i32>(conn.as_db())?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    // (count of lines, count of delivered lines) for each order
+    let line_counts = order_lines::table
+        .filter(order_lines::ol_w_id.eq(w_id))
+        .filter(order_lines::ol_d_id.eq(d_id))
+        .group_by(order_lines::ol_o_id)
+        .select((
+            order_lines::ol_o_id,
+            count_star(),
+            count(order_lines::ol_delivery_d),
+        ))
+        .load::<(i32, i64, i64)>(conn.as_db())?
+        .into_iter()
+        .map(|(o_id, lines, delivered)| (o_id, (lines, delivered)))
+        .collect::<HashMap<_, _>>();
+    let delivered_amounts = order_lines::table
+        .filter(order_lines::ol_w_id.eq(w_id))
+        .filter(order_lines::ol_d_id.eq(d_id))
+        .filter(order_lines::ol_delivery_d.is_not_null())
+        .group_by(order_lines::ol_o_id)
+        .select((
+            order_lines::ol_o_id,
+            diesel::dsl::sum(order_lines::ol_amount),
+        ))
+        .load::<(i32, Option<f64>)>(conn.as_db())?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let orders = orders::table
+        .filter(orders::o_w_id.eq(w_id))
+        .filter(orders::o_d_id.eq(d_id))
+        .select((
+            orders::o_id,
+            orders::o_c_id,
+            orders::o_carrier_id,
+            orders::o_ol_cnt,
+        ))
+        .order(orders::o_id)
+        .load::<(i32, i32, Option<i32>, i32)>(conn.as_db())?;
+
+    let mut customer_delivered = HashMap::<i32, f64>::new();
+    for (o_id, o_c_id, o_carrier_id, o_ol_cnt) in orders {
+        let is_new_order = new_order_ids.contains(&o_id);
+        c5.check(o_carrier_id.is_none() == is_new_order, || {
+            format!(
+                "W_ID={}, D_ID={}, O_ID={}: O_CARRIER_ID={:?}, NEW-ORDER exists={}",
+                w_id, d_id, o_id, o_carrier_id, is_new_order
+            )
+        });
+
+        let (lines, delivered) = line_counts.get(&o_id).copied().unwrap_or((0, 0));
+        c6.check(o_ol_cnt as i64 == lines, || {
+            format!(
+                "W_ID={}, D_ID={}, O_ID={}: O_OL_CNT={}, count(ORDER-LINE)={}",
+                w_id, d_id, o_id, o_ol_cnt, lines
+            )
+        });
+
+        let expected_delivered = if o_carrier_id.is_some() { lines } else { 0 };
+        c7.check(delivered == expected_delivered, || {
+            format!(
+                "W_ID={}, D_ID={}, O_ID={}: O_CARRIER_ID={:?}, {} of {} lines have OL_DELIVERY_D",
+                w_id, d_id, o_id, o_carrier_id, delivered, lines
+            )
+        });
+
+        if let Some(amount) = delivered_amounts.get(&o_id).copied().flatten() {
+            *customer_delivered.entry(o_c_id).or_default() += amount;
+        }
+    }
+
+    let customer_history = histories::table
+        .filter(histories::h_c_w_id.eq(w_id))
+        .filter(histories::h_c_d_id.eq(d_id))
+        .group_by(histories::h_c_id)
+        .select((histories::h_c_id, diesel::dsl::sum(histories::h_amount)))
+        .load::<(i32, Option<f64>)>(conn.as_db())?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let customers = customers::table
+        .filter(customers::c_w_id.eq(w_id))
+        .filter(customers::c_d_id.eq(d_id))
+        .select((
+            customers::c_id,
+            customers::c_balance,
+            customers::c_ytd_payment,
+        ))
+        .order(customers::c_id)
+        .load::<(i32, f64, f64)>(conn.as_db())?;
+    for (c_id, c_balance, c_ytd_payment) in customers {
+        let delivered = customer_delivered.get(&c_id).copied().unwrap_or(0.0);
+        let h_amount = customer_history
+            .get(&c_id)
+            .copied()
+            .flatten()
+            .unwrap_or(0.0);
+        c10.check(amount_eq(c_balance, delivered - h_amount), || {
+            format!(
+                "W_ID={}, D_ID={}, C_ID={}: C_BALANCE={:.2}, sum(OL_AMOUNT)={:.2}, sum(H_AMOUNT)={:.2}",
+                w_id, d_id, c_id, c_balance, delivered, h_amount
+            )
+        });
+        c12.check(amount_eq(c_balance + c_ytd_payment, delivered), || {
+            format!(
+                "W_ID={}, D_ID={}, C_ID={}: C_BALANCE={:.2}, C_YTD_PAYMENT={:.2}, sum(OL_AMOUNT)={:.2}",
+                w_id, d_id, c_id, c_balance, c_ytd_payment, delivered
+            )
+        });
+    }
+
+    Ok(())
+}
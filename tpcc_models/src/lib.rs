@@ -1,34 +1,42 @@
+mod connection;
+pub mod consistency;
+mod loader;
 mod models;
+#[cfg(feature = "mysql")]
+mod mysql;
 #[cfg(feature = "postgres")]
 mod pg;
-#[cfg(feature = "postgres")]
-mod schema_pg;
-#[cfg(not(any(feature = "postgres")))]
+pub mod population;
 mod schema_sqlite;
-#[cfg(not(any(feature = "postgres")))]
+#[cfg(feature = "sqlite")]
 mod sqlite;
+mod sqlite_options;
 mod transaction;
 
+#[cfg(not(any(feature = "sqlite", feature = "postgres", feature = "mysql")))]
+compile_error!("Enable at least one backend feature, sqlite, postgres or mysql");
+
 // Re-export Diesel types for error handling
 pub use diesel::r2d2::PoolError;
+pub use diesel::result::DatabaseErrorKind;
 pub use diesel::result::Error as QueryError;
 pub use diesel_migrations::MigrationError;
 
-pub use models::{cleanup, prepare};
+pub use loader::{load, LoadProgress};
+pub use models::{cleanup, migrate, nurand_constants, UNUSED_ITEM_ID};
 pub use models::{Customer, District, Order, OrderLine, StockedItem, Warehouse};
 
+pub use tpcc_rand::NURandConstants;
+
+pub use sqlite_options::{JournalMode, LockingMode, SqliteOptions, Synchronous, TempStore};
+pub use transaction::{IsolationLevel, TransactionOptions};
 pub use transaction::{RdConnection, RwTransaction, WrConnection};
 
-#[cfg(feature = "postgres")]
-use pg::DbConnection;
-#[cfg(feature = "postgres")]
-pub use pg::{connect, database_size, pool, vacuum, Pool};
-#[cfg(feature = "postgres")]
-use schema_pg as schema;
+pub use connection::{connect, database_settings, database_size, pool, table_sizes};
+pub use connection::{transaction_mode, vacuum};
+pub use connection::{ConnectionManager, DbConnection, Pool, TableSize};
 
-#[cfg(not(any(feature = "postgres")))]
+// All backends share the same tables and schema_sqlite.rs, generated by diesel print-schema.
+// Only order of primary key columns differs in migrations_pg.
+// In MySQL, CHAR and VARCHAR columns are Text, DATETIME columns are Timestamp.
 use schema_sqlite as schema;
-#[cfg(not(any(feature = "postgres")))]
-use sqlite::DbConnection;
-#[cfg(not(any(feature = "postgres")))]
-pub use sqlite::{connect, database_size, pool, vacuum, Pool};
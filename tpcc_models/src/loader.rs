@@ -0,0 +1,255 @@
+//! Bulk loader of initial database
+//! TPC-C standard spec. 4.3
+use crate::models::Population;
+use crate::RwTransaction;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+
+/// Cardinality of initial database, TPC-C standard spec. 4.3.3
+const ITEMS: i32 = 100_000;
+const DISTRICTS_PER_WAREHOUSE: i32 = 10;
+const CUSTOMERS_PER_DISTRICT: i32 = 3_000;
+const ORDERS_PER_DISTRICT: i32 = 3_000;
+
+/// Items or stocks committed in one transaction
+const ROWS_PER_COMMIT: i32 = 10_000;
+
+/// Random stream of NURand constants, warehouses take streams from 1,
+/// and streams of items start after the streams of warehouses
+const NURAND_STREAM: u64 = 0;
+const ITEM_STREAMS: u64 = 1 << 32;
+
+/// Timestamps of initial rows loaded with seed, unless given by caller
+const SEEDED_LOAD_TIME: &str = "2025-01-01T00:00:00";
+
+/// Progress of loading, shared with loader threads
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    warehouses: AtomicI64,
+    rows: AtomicI64,
+}
+
+impl LoadProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Warehouses whose all rows are committed
+    pub fn warehouses(&self) -> i64 {
+        self.warehouses.load(Ordering::Relaxed)
+    }
+
+    /// Rows committed in all tables
+    pub fn rows(&self) -> i64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+}
+
+/// Load initial database by `workers` threads, each thread takes own connection from `pool`
+///
+/// Items are loaded first, then warehouses are loaded in parallel.
+/// A warehouse with its stocks, districts, customers and orders is loaded by one thread,
+/// and committed by every 10_000 stocks, 3_000 customers and 3_000 orders.
+/// SQLite allows single writer, so threads generate rows in parallel and write in turn.
+///
+/// C_LOAD of NURand is chosen at first and stored in the database for the RTE.
+/// `load_time` is c_since, h_date and o_entry_d of loaded rows, now if None.
+///
+/// With `seed`, each item chunk and each warehouse is generated from its own random stream,
+/// and `load_time` is fixed to SEEDED_LOAD_TIME if None, so the same seed loads the same rows.
+/// Rows are loaded by one worker in the same order, as h_id is assigned in order of commits.
+pub fn load<E>(
+    scale_factor: i32,
+    workers: usize,
+    seed: Option<u64>,
+    load_time: Option<chrono::NaiveDateTime>,
+    pool: &crate::Pool,
+    progress: &LoadProgress,
+) -> Result<(), E>
+where
+    E: From<diesel::result::Error> + From<diesel::r2d2::PoolError> + Send,
+{
+    let started = std::time::Instant::now();
+    let workers = if seed.is_some() { 1 } else { workers };
+    let single_writer = {
+        let mut conn = pool.get()?;
+        crate::connection::before_load(&mut conn)?;
+        crate::connection::is_single_writer(&conn)
+    };
+    let nurand = match seed {
+        Some(seed) => tpcc_rand::TpcRandom::from_seed_stream(seed, NURAND_STREAM),
+        None => tpcc_rand::TpcRandom::new(),
+    }
+    .nurand();
+    let loader = Loader {
+        pool,
+        progress,
+        write_lock: single_writer.then(|| std::sync::Mutex::new(())),
+        seed,
+        nurand,
+        load_time: match (load_time, seed) {
+            (Some(load_time), _) => load_time,
+            (None, Some(_)) => SEEDED_LOAD_TIME.parse().expect("valid SEEDED_LOAD_TIME"),
+            (None, None) => chrono::Utc::now().naive_utc(),
+        },
+    };
+
+    loader.write::<E>(Population::nurand_constants(&nurand))?;
+    log::info!("NURand constants {:?}", nurand);
+
+    let item_ids = (1..=ITEMS)
+        .step_by(ROWS_PER_COMMIT as usize)
+        .collect::<Vec<_>>();
+    run_parallel::<_, E, _>(&item_ids, workers, |first_id| loader.load_items(*first_id))?;
+    log::info!("{} items loaded", ITEMS);
+
+    let warehouse_ids = (1..=scale_factor).collect::<Vec<_>>();
+    run_parallel(&warehouse_ids, workers, |w_id| {
+        loader.load_warehouse::<E>(*w_id)?;
+        let loaded = progress.warehouses.fetch_add(1, Ordering::Relaxed) + 1;
+        log::info!(
+            "warehouse {} loaded, {} of {} warehouses, {} rows in {:.1}s",
+            w_id,
+            loaded,
+            scale_factor,
+            progress.rows(),
+            started.elapsed().as_secs_f64()
+        );
+        Ok::<_, E>(())
+    })?;
+
+    let mut conn = pool.get()?;
+    crate::connection::after_load(&mut conn)?;
+    log::info!(
+        "{} rows loaded in {:.1}s",
+        progress.rows(),
+        started.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
+
+struct Loader<'a> {
+    pool: &'a crate::Pool,
+    progress: &'a LoadProgress,
+    /// Serialize write transactions of single writer database
+    write_lock: Option<std::sync::Mutex<()>>,
+    seed: Option<u64>,
+    /// C_LOAD, shared by all threads
+    nurand: tpcc_rand::NURandConstants,
+    /// c_since, h_date and o_entry_d, TPC-C standard spec. 4.3.3.1
+    load_time: chrono::NaiveDateTime,
+}
+
+impl Loader<'_> {
+    /// Random number generator for `stream`, independent of other threads
+    fn rand(&self, stream: u64) -> tpcc_rand::TpcRandom {
+        match self.seed {
+            Some(seed) => tpcc_rand::TpcRandom::from_seed_stream(seed, stream),
+            None => tpcc_rand::TpcRandom::new(),
+        }
+        .with_nurand(self.nurand)
+    }
+
+    fn load_items<E>(&self, first_id: i32) -> Result<(), E>
+    where
+        E: From<diesel::result::Error> + From<diesel::r2d2::PoolError>,
+    {
+        let mut rand = self.rand(ITEM_STREAMS + first_id as u64);
+        let last_id = (first_id + ROWS_PER_COMMIT - 1).min(ITEMS);
+        self.write(Population::items(first_id..=last_id, &mut rand))
+    }
+
+    fn load_warehouse<E>(&self, warehouse_id: i32) -> Result<(), E>
+    where
+        E: From<diesel::result::Error> + From<diesel::r2d2::PoolError>,
+    {
+        let mut rand = self.rand(warehouse_id as u64);
+        self.write::<E>(Population::warehouse(
+            warehouse_id,
+            DISTRICTS_PER_WAREHOUSE,
+            &mut rand,
+        ))?;
+
+        // Stocks of all items before order lines refer them
+        for first_id in (1..=ITEMS).step_by(ROWS_PER_COMMIT as usize) {
+            let last_id = (first_id + ROWS_PER_COMMIT - 1).min(ITEMS);
+            self.write::<E>(Population::stocks(
+                warehouse_id,
+                first_id..=last_id,
+                &mut rand,
+            ))?;
+        }
+
+        for district_id in 1..=DISTRICTS_PER_WAREHOUSE {
+            self.write::<E>(Population::customers(
+                warehouse_id,
+                district_id,
+                1..=CUSTOMERS_PER_DISTRICT,
+                self.load_time,
+                &mut rand,
+            ))?;
+            self.write::<E>(Population::orders(
+                warehouse_id,
+                district_id,
+                1..=ORDERS_PER_DISTRICT,
+                1..=CUSTOMERS_PER_DISTRICT,
+                self.load_time,
+                &mut rand,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert generated rows in a write transaction
+    fn write<E>(&self, population: Population) -> Result<(), E>
+    where
+        E: From<diesel::result::Error> + From<diesel::r2d2::PoolError>,
+    {
+        let mut conn = self.pool.get()?;
+        let _guard = self
+            .write_lock
+            .as_ref()
+            .map(|lock| lock.lock().unwrap_or_else(|e| e.into_inner()));
+        let rows = conn.write_transaction(|conn| population.insert(conn))?;
+        self.progress.rows.fetch_add(rows as i64, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Run `f` for all `tasks` in `workers` threads, stop taking new task after an error
+fn run_parallel<T, E, F>(tasks: &[T], workers: usize, f: F) -> Result<(), E>
+where
+    T: Sync,
+    E: Send,
+    F: Fn(&T) -> Result<(), E> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let threads = (0..workers.clamp(1, tasks.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    while !failed.load(Ordering::Relaxed) {
+                        let Some(task) = tasks.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                            break;
+                        };
+                        if let Err(e) = f(task) {
+                            failed.store(true, Ordering::Relaxed);
+                            return Err(e);
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Other threads are joined at the end of scope
+        threads.into_iter().try_for_each(|thread| {
+            thread
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))
+        })
+    })
+}
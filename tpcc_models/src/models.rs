@@ -1,81 +1,401 @@
+//! Tables of TPC-C database and their queries
+//!
+//! Rows of initial population are inserted by COPY FROM in PostgreSQL (see `bulk_insert!`),
+//! which requires `treat_none_as_default_value = false` on each `Insertable`.
+//! COPY FROM has no DEFAULT, `None` is written as NULL, e.g. O_CARRIER_ID of undelivered orders.
 use crate::{schema, RdConnection, WrConnection};
 use diesel::prelude::*;
 
+#[cfg(feature = "mysql")]
+const MYSQL_MIGRATIONS: diesel_migrations::EmbeddedMigrations =
+    diesel_migrations::embed_migrations!("migrations_mysql");
 #[cfg(feature = "postgres")]
-const MIGRATIONS: diesel_migrations::EmbeddedMigrations =
+const PG_MIGRATIONS: diesel_migrations::EmbeddedMigrations =
     diesel_migrations::embed_migrations!("migrations_pg");
-#[cfg(not(any(feature = "postgres")))]
-const MIGRATIONS: diesel_migrations::EmbeddedMigrations =
+#[cfg(feature = "sqlite")]
+const SQLITE_MIGRATIONS: diesel_migrations::EmbeddedMigrations =
     diesel_migrations::embed_migrations!("migrations_sqlite");
 
+/// Item ID which never appears in the initial population
+/// TPC-C standard spec. 2.4.1.5
+pub const UNUSED_ITEM_ID: i32 = 100_001;
+
 /// Cleanup existing data
-pub fn cleanup(conn: &mut WrConnection) -> diesel::migration::Result<()> {
+///
+/// Run outside of transaction, MySQL commits DDL statements implicitly.
+pub fn cleanup(conn: &mut crate::DbConnection) -> diesel::migration::Result<()> {
     use diesel_migrations::MigrationHarness;
 
-    // Run migration
-    conn.as_db().revert_all_migrations(MIGRATIONS)?;
+    // Run migration, DDL is not allowed in READ ONLY session
+    crate::connection::with_read_write_session(conn, |conn| -> diesel::migration::Result<()> {
+        match conn {
+            #[cfg(feature = "sqlite")]
+            crate::DbConnection::Sqlite(conn) => conn.revert_all_migrations(SQLITE_MIGRATIONS)?,
+            #[cfg(feature = "postgres")]
+            crate::DbConnection::Postgres(conn) => conn.revert_all_migrations(PG_MIGRATIONS)?,
+            #[cfg(feature = "mysql")]
+            crate::DbConnection::Mysql(conn) => conn.revert_all_migrations(MYSQL_MIGRATIONS)?,
+        };
+        Ok(())
+    })?;
     log::warn!("Reverted all migrations");
 
     Ok(())
 }
 
-/// Run database migration, prepare initial records
-pub fn prepare(scale_factor: i32, conn: &mut WrConnection) -> diesel::migration::Result<()> {
+/// Run database migration
+///
+/// Run outside of transaction, MySQL commits DDL statements implicitly.
+pub fn migrate(conn: &mut crate::DbConnection) -> diesel::migration::Result<()> {
     use diesel_migrations::MigrationHarness;
 
-    // Run migration
-    conn.as_db().run_pending_migrations(MIGRATIONS)?;
+    // Run migration, DDL is not allowed in READ ONLY session
+    crate::connection::with_read_write_session(conn, |conn| -> diesel::migration::Result<()> {
+        match conn {
+            #[cfg(feature = "sqlite")]
+            crate::DbConnection::Sqlite(conn) => conn.run_pending_migrations(SQLITE_MIGRATIONS)?,
+            #[cfg(feature = "postgres")]
+            crate::DbConnection::Postgres(conn) => conn.run_pending_migrations(PG_MIGRATIONS)?,
+            #[cfg(feature = "mysql")]
+            crate::DbConnection::Mysql(conn) => conn.run_pending_migrations(MYSQL_MIGRATIONS)?,
+        };
+        Ok(())
+    })?;
     log::warn!("Done all migrations");
 
-    // Prepare initial records
-    let mut rand = tpcc_rand::TpcRandom::new();
+    Ok(())
+}
+
+/// Rows of initial population, TPC-C standard spec. 4.3.3
+///
+/// Rows are generated before write transaction, and inserted by bulk_insert!
+pub(crate) enum Population {
+    /// C_LOAD of NURand, TPC-C standard spec. 2.1.6.1
+    NURandConstants(Vec<NURandConstant>),
+    Items(Vec<Item>),
+    /// Warehouse and its districts
+    Warehouse(Warehouse, Vec<District>),
+    Stocks(Vec<Stock>),
+    /// Customers and a history for each customer
+    Customers(Vec<Customer>, Vec<NewHistory>),
+    /// Orders, their order lines, and new orders of undelivered orders
+    Orders(Vec<Order>, Vec<OrderLine>, Vec<NewOrder>),
+}
 
-    // TPC-C standard spec. 4.3.3, fixed 100_000 items
-    for _ in 0..100 {
-        Item::prepare(1_000, &mut rand, conn)?;
+impl Population {
+    /// Constants of NURand used in population, a row for each A
+    pub(crate) fn nurand_constants(constants: &tpcc_rand::NURandConstants) -> Self {
+        let rows = [
+            (255, constants.c_last),
+            (1023, constants.c_id),
+            (8191, constants.ol_i_id),
+        ]
+        .into_iter()
+        .map(|(nc_a, nc_c)| NURandConstant { nc_a, nc_c })
+        .collect();
+        Self::NURandConstants(rows)
     }
-    log::info!("100_000 items written");
-
-    for _i in 0..scale_factor {
-        let warehouse = Warehouse::prepare(&mut rand, conn)?;
-        // TPC-C standard spec. 4.3.3, each warehouse has
-        //   100_000 stocks, 10 districts
-        for _ in 0..100 {
-            warehouse.prepare_stocks(1_000, &mut rand, conn)?;
-        }
-        log::info!("100_000 stocks written in warehouse {}", warehouse.w_id);
 
-        let districts = warehouse.prepare_districts(10, &mut rand, conn)?;
-        log::info!("10 districts written in warehouse {}", warehouse.w_id);
+    /// Items, 100_000 in total
+    pub(crate) fn items(
+        ids: std::ops::RangeInclusive<i32>,
+        rand: &mut tpcc_rand::TpcRandom,
+    ) -> Self {
+        let items = ids
+            .map(|i_id| {
+                // TPC-C standard spec. 4.3.3
+                Item {
+                    i_id,
+                    i_im_id: rand.i32_range(1..=10_000),
+                    i_name: rand.alnum_string(14..=24),
+                    i_price: rand.f64_range(1.00..=100.00),
+                    i_data: rand.item_data(),
+                }
+            })
+            .collect();
+        Self::Items(items)
+    }
 
-        for district in districts {
-            // TPC-C standard spec. 4.3.3, each district has
-            //   3_000 customers, 3_000 orders
-            for _ in 0..6 {
-                district.prepare_customers(500, &mut rand, conn)?;
+    /// Warehouse and `districts` districts in it, 10 districts in TPC-C
+    pub(crate) fn warehouse(
+        warehouse_id: i32,
+        districts: i32,
+        rand: &mut tpcc_rand::TpcRandom,
+    ) -> Self {
+        // TPC-C standard spec. 4.3.3
+        let warehouse = Warehouse {
+            w_id: warehouse_id,
+            w_name: rand.alnum_string(6..=10),
+            w_street_1: rand.alnum_string(10..=20),
+            w_street_2: rand.alnum_string(10..=20),
+            w_city: rand.alnum_string(10..=20),
+            w_state: rand.alnum_string(2..=2),
+            w_zip: rand.zip_code(),
+            w_tax: rand.f64_range(0.0..=0.2),
+            w_ytd: 300_000.0,
+        };
+
+        let districts = (1..=districts)
+            .map(|d_id| {
+                // TPC-C standard spec. 4.3.3
+                District {
+                    d_id,
+                    d_w_id: warehouse_id,
+                    d_name: rand.alnum_string(6..=10),
+                    d_street_1: rand.alnum_string(10..=20),
+                    d_street_2: rand.alnum_string(10..=20),
+                    d_city: rand.alnum_string(10..=20),
+                    d_state: rand.alnum_string(2..=2),
+                    d_zip: rand.zip_code(),
+                    d_tax: rand.f64_range(0.0000..=0.2000),
+                    d_ytd: 30_000.00,
+                    d_next_o_id: 3001,
+                }
+            })
+            .collect();
+        Self::Warehouse(warehouse, districts)
+    }
+
+    /// Stocks in warehouse, 100_000 for each warehouse
+    pub(crate) fn stocks(
+        warehouse_id: i32,
+        item_ids: std::ops::RangeInclusive<i32>,
+        rand: &mut tpcc_rand::TpcRandom,
+    ) -> Self {
+        let stocks = item_ids
+            .map(|s_i_id| {
+                // TPC-C standard spec. 4.3.3
+                Stock {
+                    s_i_id,
+                    s_w_id: warehouse_id,
+                    s_quantity: rand.i32_range(10..=100),
+                    s_dist_01: rand.alnum_string(24..=24),
+                    s_dist_02: rand.alnum_string(24..=24),
+                    s_dist_03: rand.alnum_string(24..=24),
+                    s_dist_04: rand.alnum_string(24..=24),
+                    s_dist_05: rand.alnum_string(24..=24),
+                    s_dist_06: rand.alnum_string(24..=24),
+                    s_dist_07: rand.alnum_string(24..=24),
+                    s_dist_08: rand.alnum_string(24..=24),
+                    s_dist_09: rand.alnum_string(24..=24),
+                    s_dist_10: rand.alnum_string(24..=24),
+                    s_ytd: 0,
+                    s_order_cnt: 0,
+                    s_remote_cnt: 0,
+                    s_data: rand.item_data(),
+                }
+            })
+            .collect();
+        Self::Stocks(stocks)
+    }
+
+    /// Customers in district and their histories, 3_000 customers for each district
+    pub(crate) fn customers(
+        warehouse_id: i32,
+        district_id: i32,
+        customer_ids: std::ops::RangeInclusive<i32>,
+        load_time: chrono::NaiveDateTime,
+        rand: &mut tpcc_rand::TpcRandom,
+    ) -> Self {
+        let customers = customer_ids
+            .map(|c_id| {
+                // TPC-C standard spec. 4.3.3
+                let c_credit = if 0 == rand.i32_range(0..=9) {
+                    "BC" // 10%
+                } else {
+                    "GC" // 90%
+                }
+                .to_string();
+                let c_last = if 0 < c_id && c_id <= 1000 {
+                    tpcc_rand::TpcRandom::last_name(c_id - 1) // spec. 4.3.2.3
+                } else {
+                    tpcc_rand::TpcRandom::last_name(rand.last_name_index())
+                };
+                Customer {
+                    c_id,
+                    c_d_id: district_id,
+                    c_w_id: warehouse_id,
+                    c_first: rand.alnum_string(8..=16),
+                    c_middle: "OE".to_string(),
+                    c_last,
+                    c_street_1: rand.alnum_string(10..=20),
+                    c_street_2: rand.alnum_string(10..=20),
+                    c_city: rand.alnum_string(10..=20),
+                    c_state: rand.alnum_string(2..=2),
+                    c_zip: rand.zip_code(),
+                    c_phone: rand.num_string(16),
+                    c_since: load_time,
+                    c_credit,
+                    c_credit_lim: 50_000.00,
+                    c_discount: rand.f64_range(0.0..=0.5),
+                    c_balance: -10.00,
+                    c_ytd_payment: 10.00,
+                    c_payment_cnt: 1,
+                    c_delivery_cnt: 0,
+                    c_data: rand.alnum_string(300..=500),
+                }
+            })
+            .collect::<Vec<Customer>>();
+
+        let histories = customers
+            .iter()
+            .map(|customer| {
+                // TPC-C standard spec. 4.3.3
+                NewHistory {
+                    h_c_id: customer.c_id,
+                    h_c_d_id: customer.c_d_id,
+                    h_c_w_id: customer.c_w_id,
+                    h_d_id: customer.c_d_id,
+                    h_w_id: customer.c_w_id,
+                    h_date: load_time,
+                    h_amount: 10.0,
+                    h_data: rand.alnum_string(12..=24),
+                }
+            })
+            .collect();
+        Self::Customers(customers, histories)
+    }
+
+    /// Orders in district, 3_000 orders for each district,
+    /// ordered by a random permutation of customers in `customers`,
+    /// `customers` has the same number of ids as `order_ids`
+    pub(crate) fn orders(
+        warehouse_id: i32,
+        district_id: i32,
+        order_ids: std::ops::RangeInclusive<i32>,
+        customers: std::ops::RangeInclusive<i32>,
+        load_time: chrono::NaiveDateTime,
+        rand: &mut tpcc_rand::TpcRandom,
+    ) -> Self {
+        // TPC-C standard spec. 4.3.3.1, O_C_ID selected sequentially from a random permutation
+        let mut customer_ids = customers.collect::<Vec<_>>();
+        rand.shuffle(&mut customer_ids);
+
+        let orders = order_ids
+            .zip(customer_ids)
+            .map(|(o_id, o_c_id)| {
+                // TPC-C standard spec. 4.3.3
+                let o_carrier_id = if o_id <= 2100 {
+                    Some(rand.i32_range(1..=10))
+                } else {
+                    None
+                };
+
+                Order {
+                    o_id,
+                    o_d_id: district_id,
+                    o_w_id: warehouse_id,
+                    o_c_id,
+                    o_entry_d: load_time,
+                    o_carrier_id,
+                    o_ol_cnt: rand.i32_range(5..=15),
+                    o_all_local: 1,
+                }
+            })
+            .collect::<Vec<Order>>();
+
+        let order_lines = orders
+            .iter()
+            .flat_map(|order| {
+                let ol_delivery_id = if order.o_id <= 2100 {
+                    Some(order.o_entry_d)
+                } else {
+                    None
+                };
+                (1..=order.o_ol_cnt)
+                    .map(|ol_number| OrderLine {
+                        ol_o_id: order.o_id,
+                        ol_d_id: order.o_d_id,
+                        ol_w_id: order.o_w_id,
+                        ol_number,
+                        ol_i_id: rand.i32_range(1..=100_000),
+                        ol_supply_w_id: order.o_w_id,
+                        ol_delivery_d: ol_delivery_id,
+                        ol_quantity: 5,
+                        ol_amount: if order.o_id <= 2100 {
+                            0.0
+                        } else {
+                            rand.f64_range(0.01..=9_999.99)
+                        },
+                        ol_dist_info: rand.alnum_string(24..=24),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let new_orders = orders
+            .iter()
+            .filter(|order| order.o_carrier_id.is_none())
+            .map(|order| NewOrder {
+                no_o_id: order.o_id,
+                no_d_id: order.o_d_id,
+                no_w_id: order.o_w_id,
+            })
+            .collect();
+        Self::Orders(orders, order_lines, new_orders)
+    }
+
+    /// Insert all rows, returns number of inserted rows
+    pub(crate) fn insert(&self, conn: &mut WrConnection) -> QueryResult<usize> {
+        use crate::connection::bulk_insert;
+        use schema::*;
+
+        let conn = conn.as_db();
+        let rows = match self {
+            Self::NURandConstants(rows) => bulk_insert!(conn, nurand_constants::table, rows)?,
+            Self::Items(items) => bulk_insert!(conn, items::table, items)?,
+            Self::Warehouse(warehouse, districts) => {
+                bulk_insert!(conn, warehouses::table, std::slice::from_ref(warehouse))?
+                    + bulk_insert!(conn, districts::table, districts)?
             }
-            log::info!(
-                "3000 custmers written in warehouse {}, district {}",
-                warehouse.w_id,
-                district.d_id
-            );
-            for _ in 0..6 {
-                district.prepare_orders(500, &mut rand, conn)?;
+            Self::Stocks(stocks) => bulk_insert!(conn, stocks::table, stocks)?,
+            Self::Customers(customers, histories) => {
+                bulk_insert!(conn, customers::table, customers)?
+                    + bulk_insert!(conn, histories::table, histories)?
             }
-            log::info!(
-                "3000 orders written in warehouse {}, district {}",
-                warehouse.w_id,
-                district.d_id
-            );
-        }
+            Self::Orders(orders, order_lines, new_orders) => {
+                bulk_insert!(conn, orders::table, orders)?
+                    + bulk_insert!(conn, order_lines::table, order_lines)?
+                    + bulk_insert!(conn, new_orders::table, new_orders)?
+            }
+        };
+        Ok(rows)
     }
+}
 
-    Ok(())
+/// Constant C of NURand(A, x, y) for each A
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = schema::nurand_constants)]
+#[diesel(treat_none_as_default_value = false)]
+pub(crate) struct NURandConstant {
+    nc_a: i32,
+    nc_c: i32,
+}
+
+/// C_LOAD of NURand used in initial population, None if not prepared
+/// TPC-C standard spec. 2.1.6.1
+pub fn nurand_constants(
+    conn: &mut RdConnection,
+) -> QueryResult<Option<tpcc_rand::NURandConstants>> {
+    let rows = schema::nurand_constants::table
+        .select(NURandConstant::as_select())
+        .load(conn.as_db())?;
+    let c = |a| rows.iter().find(|row| row.nc_a == a).map(|row| row.nc_c);
+
+    Ok(match (c(255), c(1023), c(8191)) {
+        (Some(c_last), Some(c_id), Some(ol_i_id)) => Some(tpcc_rand::NURandConstants {
+            c_last,
+            c_id,
+            ol_i_id,
+        }),
+        _ => None,
+    })
 }
 
 /// Sales item
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::items)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct Item {
     i_id: i32,
     i_im_id: i32,
@@ -91,42 +411,12 @@ impl Item {
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    // Prepare initial Items
-    pub fn prepare(
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Self>> {
-        use schema::items;
-        let cur_id = items::table
-            .select(diesel::dsl::max(items::i_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        let prepared_items = (0..num)
-            .map(|i| {
-                // TPC-C standard spec. 4.3.3
-                Self {
-                    i_id: cur_id + i + 1,
-                    i_im_id: rand.i32_range(1..=10_000),
-                    i_name: rand.alnum_string(14..=24),
-                    i_price: rand.f64_range(1.00..=100.00),
-                    i_data: rand.item_data(),
-                }
-            })
-            .collect::<Vec<Self>>();
-
-        diesel::insert_into(items::table)
-            .values(&prepared_items)
-            .execute(conn.as_db())?;
-        Ok(prepared_items)
-    }
 }
 
 /// Warehouse
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::warehouses)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct Warehouse {
     w_id: i32,
     w_name: String,
@@ -170,60 +460,12 @@ impl Warehouse {
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    /// Prepare Warehose
-    pub fn prepare(rand: &mut tpcc_rand::TpcRandom, conn: &mut WrConnection) -> QueryResult<Self> {
-        use schema::warehouses;
-        let cur_id = warehouses::table
-            .select(diesel::dsl::max(warehouses::w_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        // TPC-C standard spec. 4.3.3
-        let prepared_warehouse = Self {
-            w_id: cur_id + 1,
-            w_name: rand.alnum_string(6..=10),
-            w_street_1: rand.alnum_string(10..=20),
-            w_street_2: rand.alnum_string(10..=20),
-            w_city: rand.alnum_string(10..=20),
-            w_state: rand.alnum_string(2..=2),
-            w_zip: rand.zip_code(),
-            w_tax: rand.f64_range(0.0..=0.2),
-            w_ytd: 300_000.0,
-        };
-
-        diesel::insert_into(warehouses::table)
-            .values(&prepared_warehouse)
-            .execute(conn.as_db())?;
-        Ok(prepared_warehouse)
-    }
-
-    /// Insert stock in warehouse
-    /// In TPC-C standard, each warehouse has 100_000 stocks.
-    pub fn prepare_stocks(
-        &self,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Stock>> {
-        Stock::prepare(self.w_id, num, rand, conn)
-    }
-
-    /// Insert district in warehouse
-    /// In TPC-C standard, each warehouse has 10 districts.
-    pub fn prepare_districts(
-        &self,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<District>> {
-        District::prepare(self.w_id, num, rand, conn)
-    }
 }
 
 /// Stock in Warehouse
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::stocks)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct Stock {
     s_i_id: i32,
     s_w_id: i32,
@@ -269,14 +511,18 @@ impl Stock {
         let row = stocks::table
             .filter(stocks::s_w_id.eq(self.s_w_id))
             .filter(stocks::s_i_id.eq(self.s_i_id));
-        let updated_stock = diesel::update(row)
-            .set((
+        let updated_stock = crate::connection::update_returning!(
+            conn.as_db(),
+            row,
+            (
                 stocks::s_quantity.eq(new_qty),
                 stocks::s_ytd.eq(stocks::s_ytd + quantity),
                 stocks::s_order_cnt.eq(stocks::s_order_cnt + 1),
                 stocks::s_remote_cnt.eq(stocks::s_remote_cnt + remote_inc),
-            ))
-            .get_result(conn.as_db())?;
+            ),
+            get_result,
+            first
+        )?;
 
         Ok(updated_stock)
     }
@@ -287,52 +533,6 @@ impl Stock {
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    /// Insert new stocks
-    ///   public API: call warehouse.insert_stocks() instead.
-    fn prepare(
-        warehouse_id: i32,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Self>> {
-        use schema::stocks;
-        let cur_id = stocks::table
-            .filter(stocks::s_w_id.eq(warehouse_id))
-            .select(diesel::dsl::max(stocks::s_i_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        let prepared_stocks = (0..num)
-            .map(|i| {
-                // TPC-C standard spec. 4.3.3
-                Self {
-                    s_i_id: cur_id + i + 1,
-                    s_w_id: warehouse_id,
-                    s_quantity: rand.i32_range(10..=100),
-                    s_dist_01: rand.alnum_string(24..=24),
-                    s_dist_02: rand.alnum_string(24..=24),
-                    s_dist_03: rand.alnum_string(24..=24),
-                    s_dist_04: rand.alnum_string(24..=24),
-                    s_dist_05: rand.alnum_string(24..=24),
-                    s_dist_06: rand.alnum_string(24..=24),
-                    s_dist_07: rand.alnum_string(24..=24),
-                    s_dist_08: rand.alnum_string(24..=24),
-                    s_dist_09: rand.alnum_string(24..=24),
-                    s_dist_10: rand.alnum_string(24..=24),
-                    s_ytd: 0,
-                    s_order_cnt: 0,
-                    s_remote_cnt: 0,
-                    s_data: rand.item_data(),
-                }
-            })
-            .collect::<Vec<Self>>();
-
-        diesel::insert_into(stocks::table)
-            .values(&prepared_stocks)
-            .execute(conn.as_db())?;
-        Ok(prepared_stocks)
-    }
 }
 
 /// Interface type
@@ -357,6 +557,7 @@ impl StockedItem {
 /// District: belongs to Warehouse
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::districts)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct District {
     d_id: i32,
     d_w_id: i32,
@@ -435,19 +636,23 @@ impl District {
         let row = districts::table
             .filter(districts::d_w_id.eq(self.d_w_id))
             .filter(districts::d_id.eq(self.d_id));
-        let next_id = diesel::update(row)
-            .set(districts::d_next_o_id.eq(districts::d_next_o_id + 1))
-            .returning(districts::d_next_o_id)
-            .get_result(conn.as_db())?;
+        let updated: District = crate::connection::update_returning!(
+            conn.as_db(),
+            row,
+            districts::d_next_o_id.eq(districts::d_next_o_id + 1),
+            get_result,
+            first
+        )?;
+        let next_id = updated.d_next_o_id;
 
         self.d_next_o_id = next_id;
 
         Ok(next_id - 1)
     }
 
-    /// Delivery transaction
+    /// Delivery transaction, returns delivered order IDs
     /// TPC-C standard spec. 2.7.4
-    pub fn delivery(&self, carrier_id: i32, conn: &mut WrConnection) -> QueryResult<usize> {
+    pub fn delivery(&self, carrier_id: i32, conn: &mut WrConnection) -> QueryResult<Vec<i32>> {
         conn.transaction(move |mut conn| {
             use schema::{customers, new_orders, orders};
 
@@ -468,14 +673,17 @@ impl District {
             )
             .execute(conn.as_db())?;
 
-            let orders_to_deliver: Vec<Order> = diesel::update(
-                orders::table
-                    .filter(orders::o_w_id.eq(self.d_w_id))
-                    .filter(orders::o_d_id.eq(self.d_id))
-                    .filter(orders::o_id.eq_any(order_ids)),
-            )
-            .set(orders::o_carrier_id.eq(carrier_id))
-            .get_results(conn.as_db())?;
+            let rows = orders::table
+                .filter(orders::o_w_id.eq(self.d_w_id))
+                .filter(orders::o_d_id.eq(self.d_id))
+                .filter(orders::o_id.eq_any(order_ids));
+            let orders_to_deliver: Vec<Order> = crate::connection::update_returning!(
+                conn.as_db(),
+                rows,
+                orders::o_carrier_id.eq(carrier_id),
+                get_results,
+                load
+            )?;
 
             let tm = chrono::Utc::now().naive_utc();
             for order in &orders_to_deliver {
@@ -495,7 +703,7 @@ impl District {
                 ))
                 .execute(conn.as_db())?;
             }
-            Ok(orders_to_deliver.len())
+            Ok(orders_to_deliver.iter().map(|order| order.o_id).collect())
         })
     }
 
@@ -526,78 +734,22 @@ impl District {
         Ok(low_stocks as usize)
     }
 
+    /// PK
+    pub fn id(&self) -> (i32, i32) {
+        (self.d_w_id, self.d_id)
+    }
+
     /// Count all rows
     pub fn count(conn: &mut RdConnection) -> QueryResult<i64> {
         schema::districts::table
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    /// Insert new districts
-    ///   public API: call warehouse.prepare_district() instead.
-    fn prepare(
-        warehouse_id: i32,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Self>> {
-        use schema::districts;
-        let cur_id = districts::table
-            .filter(districts::d_w_id.eq(warehouse_id))
-            .select(diesel::dsl::max(districts::d_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        let prepared_districts = (0..num)
-            .map(|i| {
-                // TPC-C standard spec. 4.3.3
-                Self {
-                    d_id: cur_id + i + 1,
-                    d_w_id: warehouse_id,
-                    d_name: rand.alnum_string(6..=10),
-                    d_street_1: rand.alnum_string(10..=20),
-                    d_street_2: rand.alnum_string(10..=20),
-                    d_city: rand.alnum_string(10..=20),
-                    d_state: rand.alnum_string(2..=2),
-                    d_zip: rand.zip_code(),
-                    d_tax: rand.f64_range(0.0000..=0.2000),
-                    d_ytd: 30_000.00,
-                    d_next_o_id: 3001,
-                }
-            })
-            .collect::<Vec<Self>>();
-
-        diesel::insert_into(districts::table)
-            .values(&prepared_districts)
-            .execute(conn.as_db())?;
-        Ok(prepared_districts)
-    }
-
-    /// Insert customers in district
-    /// In TPC-C standard, each district has 3_000 customers.
-    pub fn prepare_customers(
-        &self,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Customer>> {
-        Customer::prepare(self.d_w_id, self.d_id, num, rand, conn)
-    }
-
-    /// Insert orders in district
-    /// In TPC-C standard, each district has 3_000 orders.
-    pub fn prepare_orders(
-        &self,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Order>> {
-        Order::prepare(self.d_w_id, self.d_id, num, rand, conn)
-    }
 }
 
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::customers)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct Customer {
     c_id: i32,
     c_d_id: i32,
@@ -666,23 +818,32 @@ impl Customer {
 
         conn.transaction(move |mut conn| {
             // Increment warehouse ytd
-            let warehouse = diesel::update(warehouses::table.find(district_at.d_w_id))
-                .set(warehouses::w_ytd.eq(warehouses::w_ytd + amount))
-                .get_result::<Warehouse>(conn.as_db())?;
+            let row = warehouses::table.find(district_at.d_w_id);
+            let warehouse: Warehouse = crate::connection::update_returning!(
+                conn.as_db(),
+                row,
+                warehouses::w_ytd.eq(warehouses::w_ytd + amount),
+                get_result,
+                first
+            )?;
             // Increment district ytd
             let row = districts::table
                 .filter(districts::d_w_id.eq(district_at.d_w_id))
                 .filter(districts::d_id.eq(district_at.d_id));
-            let district = diesel::update(row)
-                .set(districts::d_ytd.eq(districts::d_ytd + amount))
-                .get_result::<District>(conn.as_db())?;
+            let district: District = crate::connection::update_returning!(
+                conn.as_db(),
+                row,
+                districts::d_ytd.eq(districts::d_ytd + amount),
+                get_result,
+                first
+            )?;
 
             // Update customer column
             let row = customers::table
                 .filter(customers::c_w_id.eq(self.c_w_id))
                 .filter(customers::c_d_id.eq(self.c_d_id))
                 .filter(customers::c_id.eq(self.c_id));
-            let updated_customer = if self.c_credit == "BC" {
+            let updated_customer: Self = if self.c_credit == "BC" {
                 // Update c_data field
                 let new_c_data = format!(
                     "{:04}{:04}{:04}{:04}{:04}{:04.2}{}",
@@ -696,20 +857,28 @@ impl Customer {
                 );
                 let new_c_data_trimed = &new_c_data[0..self.c_data.len()];
 
-                diesel::update(row)
-                    .set((
+                crate::connection::update_returning!(
+                    conn.as_db(),
+                    row,
+                    (
                         customers::c_balance.eq(customers::c_balance - amount),
                         customers::c_ytd_payment.eq(customers::c_ytd_payment + amount),
                         customers::c_data.eq(new_c_data_trimed),
-                    ))
-                    .get_result::<Self>(conn.as_db())?
+                    ),
+                    get_result,
+                    first
+                )?
             } else {
-                diesel::update(row)
-                    .set((
+                crate::connection::update_returning!(
+                    conn.as_db(),
+                    row,
+                    (
                         customers::c_balance.eq(customers::c_balance - amount),
                         customers::c_ytd_payment.eq(customers::c_ytd_payment + amount),
-                    ))
-                    .get_result::<Self>(conn.as_db())?
+                    ),
+                    get_result,
+                    first
+                )?
             };
 
             // Insert history
@@ -764,103 +933,11 @@ impl Customer {
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    /// Insert new customers
-    ///   public API: call district.prepare_customers() instead.
-    fn prepare(
-        warehouse_id: i32,
-        district_id: i32,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Self>> {
-        use schema::{customers, histories};
-
-        let cur_c_id = customers::table
-            .filter(customers::c_w_id.eq(warehouse_id))
-            .filter(customers::c_d_id.eq(district_id))
-            .select(diesel::dsl::max(customers::c_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        let prepared_customers = (0..num)
-            .map(|i| {
-                let c_id = cur_c_id + i + 1;
-
-                // TPC-C standard spec. 4.3.3
-                let c_credit = if 0 == rand.i32_range(0..=9) {
-                    "GC" // 10%
-                } else {
-                    "BC" // 90%
-                }
-                .to_string();
-                let c_last = if 0 < c_id && c_id <= 1000 {
-                    tpcc_rand::TpcRandom::last_name(c_id - 1) // spec. 4.3.2.3
-                } else {
-                    tpcc_rand::TpcRandom::last_name(rand.non_uniform_i32(255, 0..=999))
-                };
-                Self {
-                    c_id,
-                    c_d_id: district_id,
-                    c_w_id: warehouse_id,
-                    c_first: rand.alnum_string(8..=16),
-                    c_middle: "OE".to_string(),
-                    c_last,
-                    c_street_1: rand.alnum_string(10..=20),
-                    c_street_2: rand.alnum_string(10..=20),
-                    c_city: rand.alnum_string(10..=20),
-                    c_state: rand.alnum_string(2..=2),
-                    c_zip: rand.zip_code(),
-                    c_phone: rand.num_string(16),
-                    c_since: chrono::Utc::now().naive_utc(),
-                    c_credit,
-                    c_credit_lim: 50_000.00,
-                    c_discount: rand.f64_range(0.0..=0.5),
-                    c_balance: -10.00,
-                    c_ytd_payment: 10.00,
-                    c_payment_cnt: 1,
-                    c_delivery_cnt: 0,
-                    c_data: rand.alnum_string(300..=500),
-                }
-            })
-            .collect::<Vec<Self>>();
-        diesel::insert_into(customers::table)
-            .values(&prepared_customers)
-            .execute(conn.as_db())?;
-
-        // Also insert histories
-        let cur_h_id = histories::table
-            .select(diesel::dsl::max(histories::h_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-        let prepared_histories = prepared_customers
-            .iter()
-            .enumerate()
-            .map(|(i, customer)| {
-                // TPC-C standard spec. 4.3.3
-                History {
-                    h_id: cur_h_id + 1 + i as i32,
-                    h_c_id: customer.c_id,
-                    h_c_d_id: customer.c_d_id,
-                    h_c_w_id: customer.c_w_id,
-                    h_d_id: customer.c_d_id,
-                    h_w_id: customer.c_w_id,
-                    h_date: chrono::Utc::now().naive_utc(),
-                    h_amount: 10.0,
-                    h_data: rand.alnum_string(12..=24),
-                }
-            })
-            .collect::<Vec<History>>();
-        diesel::insert_into(histories::table)
-            .values(&prepared_histories)
-            .execute(conn.as_db())?;
-
-        Ok(prepared_customers)
-    }
 }
 
-#[derive(Debug, Insertable, Queryable, Selectable)]
+#[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = schema::histories)]
+#[allow(dead_code)] // All columns are returned from INSERT, only a few are used
 pub struct History {
     h_id: i32,
     h_c_id: i32,
@@ -873,6 +950,21 @@ pub struct History {
     h_data: String,
 }
 
+/// History row to insert, h_id is assigned by database
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::histories)]
+#[diesel(treat_none_as_default_value = false)]
+pub(crate) struct NewHistory {
+    h_c_id: i32,
+    h_c_d_id: i32,
+    h_c_w_id: i32,
+    h_d_id: i32,
+    h_w_id: i32,
+    h_date: chrono::NaiveDateTime,
+    h_amount: f64,
+    h_data: String,
+}
+
 impl History {
     fn insert(
         customer: &Customer,
@@ -883,14 +975,7 @@ impl History {
     ) -> QueryResult<Self> {
         use schema::histories;
 
-        // max history_id
-        let cur_h_id = histories::table
-            .select(diesel::dsl::max(histories::h_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        let history = Self {
-            h_id: cur_h_id + 1,
+        let history = NewHistory {
             h_c_id: customer.c_id,
             h_c_d_id: customer.c_d_id,
             h_c_w_id: customer.c_w_id,
@@ -901,11 +986,35 @@ impl History {
             h_data: format!("{}    {}", warehouse_at.w_name, district_at.d_name),
         };
 
-        diesel::insert_into(histories::table)
-            .values(&history)
-            .execute(conn.as_db())?;
-
-        Ok(history)
+        // h_id is assigned by INTEGER PRIMARY KEY (SQLite), IDENTITY (PostgreSQL)
+        // or AUTO_INCREMENT (MySQL)
+        match conn.as_db() {
+            #[cfg(feature = "sqlite")]
+            crate::DbConnection::Sqlite(db) => diesel::insert_into(histories::table)
+                .values(&history)
+                .returning(Self::as_returning())
+                .get_result(db),
+            #[cfg(feature = "postgres")]
+            crate::DbConnection::Postgres(db) => diesel::insert_into(histories::table)
+                .values(&history)
+                .returning(Self::as_returning())
+                .get_result(db),
+            #[cfg(feature = "mysql")]
+            crate::DbConnection::Mysql(db) => {
+                // MySQL has no RETURNING clause
+                diesel::insert_into(histories::table)
+                    .values(&history)
+                    .execute(db)?;
+                let h_id = diesel::select(diesel::dsl::sql::<diesel::sql_types::BigInt>(
+                    "CAST(LAST_INSERT_ID() AS SIGNED)",
+                ))
+                .get_result::<i64>(db)?;
+                histories::table
+                    .find(h_id as i32)
+                    .select(Self::as_select())
+                    .first(db)
+            }
+        }
     }
 
     /// history timestamp
@@ -923,6 +1032,7 @@ impl History {
 
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::orders)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct Order {
     o_id: i32,
     o_d_id: i32,
@@ -958,9 +1068,11 @@ impl Order {
             o_ol_cnt: items.len() as i32,
             o_all_local: items.iter().all(|(s, _qty)| s.stock.s_w_id == warehouse_id) as i32,
         };
-        diesel::insert_into(orders::table)
-            .values(&insert_order)
-            .execute(conn.as_db())?;
+        crate::connection::with_backend!(conn.as_db(), |db| {
+            diesel::insert_into(orders::table)
+                .values(&insert_order)
+                .execute(db)
+        })?;
 
         // NewOrder
         let insert_new_order = NewOrder {
@@ -968,19 +1080,25 @@ impl Order {
             no_d_id: district_id,
             no_w_id: warehouse_id,
         };
-        diesel::insert_into(new_orders::table)
-            .values(&insert_new_order)
-            .execute(conn.as_db())?;
+        crate::connection::with_backend!(conn.as_db(), |db| {
+            diesel::insert_into(new_orders::table)
+                .values(&insert_new_order)
+                .execute(db)
+        })?;
 
         // OrderLines
         let insert_order_lines = items
             .iter()
             .enumerate()
-            .map(|(idx, (item, qty))| OrderLine::new(customer, item, order_id, idx as i32, *qty))
+            .map(|(idx, (item, qty))| {
+                OrderLine::new(customer, item, order_id, idx as i32 + 1, *qty)
+            })
             .collect::<Vec<_>>();
-        diesel::insert_into(order_lines::table)
-            .values(&insert_order_lines)
-            .execute(conn.as_db())?;
+        crate::connection::with_backend!(conn.as_db(), |db| {
+            diesel::insert_into(order_lines::table)
+                .values(&insert_order_lines)
+                .execute(db)
+        })?;
 
         Ok((insert_order, insert_order_lines))
     }
@@ -1005,14 +1123,17 @@ impl Order {
     ) -> QueryResult<Vec<OrderLine>> {
         use schema::order_lines;
 
-        let updated_lines = diesel::update(
-            order_lines::table
-                .filter(order_lines::ol_w_id.eq(self.o_w_id))
-                .filter(order_lines::ol_d_id.eq(self.o_d_id))
-                .filter(order_lines::ol_o_id.eq(self.o_id)),
-        )
-        .set(order_lines::ol_delivery_d.eq(tm))
-        .get_results::<OrderLine>(conn.as_db())?;
+        let rows = order_lines::table
+            .filter(order_lines::ol_w_id.eq(self.o_w_id))
+            .filter(order_lines::ol_d_id.eq(self.o_d_id))
+            .filter(order_lines::ol_o_id.eq(self.o_id));
+        let updated_lines: Vec<OrderLine> = crate::connection::update_returning!(
+            conn.as_db(),
+            rows,
+            order_lines::ol_delivery_d.eq(tm),
+            get_results,
+            load
+        )?;
 
         Ok(updated_lines)
     }
@@ -1036,124 +1157,11 @@ impl Order {
             .select(diesel::dsl::count_star())
             .first(conn.as_db())
     }
-
-    /// Insert new orders
-    ///   public API: call district.prepare_orders() instead.
-    fn prepare(
-        warehouse_id: i32,
-        district_id: i32,
-        num: i32,
-        rand: &mut tpcc_rand::TpcRandom,
-        conn: &mut WrConnection,
-    ) -> QueryResult<Vec<Self>> {
-        use schema::{customers, new_orders, order_lines, orders};
-        let cur_id = orders::table
-            .filter(orders::o_w_id.eq(warehouse_id))
-            .filter(orders::o_d_id.eq(district_id))
-            .select(diesel::dsl::max(orders::o_id))
-            .first::<Option<i32>>(conn.as_db())?
-            .unwrap_or(0);
-
-        // min-max customer ID
-        let (min_c_id, max_c_id) = customers::table
-            .filter(customers::c_w_id.eq(warehouse_id))
-            .filter(customers::c_d_id.eq(district_id))
-            .select((
-                diesel::dsl::min(customers::c_id),
-                diesel::dsl::max(customers::c_id),
-            ))
-            .first::<(Option<i32>, Option<i32>)>(conn.as_db())?;
-
-        let min_c_id = min_c_id.unwrap_or(0);
-        let max_c_id = max_c_id.unwrap_or(0);
-
-        // Orders
-        let prepared_orders = (0..num)
-            .map(|i| {
-                // TPC-C standard spec. 4.3.3
-                let o_id = cur_id + i + 1;
-                let o_carrier_id = if o_id <= 2100 {
-                    Some(rand.i32_range(1..=10))
-                } else {
-                    None
-                };
-
-                Self {
-                    o_id,
-                    o_d_id: district_id,
-                    o_w_id: warehouse_id,
-                    o_c_id: rand.i32_range(min_c_id..=max_c_id),
-                    o_entry_d: chrono::Utc::now().naive_utc(),
-                    o_carrier_id,
-                    o_ol_cnt: rand.i32_range(5..=15),
-                    o_all_local: 1,
-                }
-            })
-            .collect::<Vec<Self>>();
-        diesel::insert_into(orders::table)
-            .values(&prepared_orders)
-            .execute(conn.as_db())?;
-
-        // OrderLines
-        let prepared_orderlines = prepared_orders
-            .iter()
-            .map(|order| {
-                let ol_delivery_id = if order.o_id <= 2100 {
-                    Some(order.o_entry_d)
-                } else {
-                    None
-                };
-                let ol_amount = if order.o_id <= 2100 {
-                    0.0
-                } else {
-                    rand.f64_range(0.01..=9_999.99)
-                };
-                (0..order.o_ol_cnt)
-                    .map(|i| OrderLine {
-                        ol_o_id: order.o_id,
-                        ol_d_id: order.o_d_id,
-                        ol_w_id: order.o_w_id,
-                        ol_number: i,
-                        ol_i_id: rand.i32_range(1..=100_000),
-                        ol_supply_w_id: order.o_w_id,
-                        ol_delivery_d: ol_delivery_id,
-                        ol_quantity: 5,
-                        ol_amount,
-                        ol_dist_info: rand.alnum_string(24..=24),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .flatten()
-            .collect::<Vec<OrderLine>>();
-        diesel::insert_into(order_lines::table)
-            .values(&prepared_orderlines)
-            .execute(conn.as_db())?;
-
-        // NewOrders
-        let prepared_new_orders = prepared_orders
-            .iter()
-            .filter_map(|order| {
-                if order.o_carrier_id.is_none() {
-                    Some(NewOrder {
-                        no_o_id: order.o_id,
-                        no_d_id: order.o_d_id,
-                        no_w_id: order.o_w_id,
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<NewOrder>>();
-        diesel::insert_into(new_orders::table)
-            .values(&prepared_new_orders)
-            .execute(conn.as_db())?;
-
-        Ok(prepared_orders)
-    }
 }
 
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::order_lines)]
+#[diesel(treat_none_as_default_value = false)]
 pub struct OrderLine {
     ol_o_id: i32,
     ol_d_id: i32,
@@ -1230,7 +1238,8 @@ impl OrderLine {
 
 #[derive(Debug, Insertable, Queryable, Selectable)]
 #[diesel(table_name = schema::new_orders)]
-struct NewOrder {
+#[diesel(treat_none_as_default_value = false)]
+pub(crate) struct NewOrder {
     no_o_id: i32,
     no_d_id: i32,
     no_w_id: i32,
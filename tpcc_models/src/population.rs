@@ -0,0 +1,372 @@
+//! Validation of initial database population
+//! TPC-C standard spec. 4.3.3.1
+//!
+//! Rules are only satisfied by the database just after preparation,
+//! transactions of benchmark add orders and deliver new orders.
+use crate::{schema, RdConnection};
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+/// Maximum number of sample rows recorded for each rule
+const MAX_SAMPLES: usize = 10;
+
+/// Orders 1..=2100 are delivered, the last 900 orders are new orders in each district
+const DELIVERED_ORDERS: i32 = 2100;
+const NEW_ORDERS_PER_DISTRICT: i64 = 900;
+
+/// Allowed difference of shares in percentage points, from randomness of population.
+/// Standard deviation of 10% share in 30_000 customers of a warehouse is 0.17 points.
+const SHARE_TOLERANCE: f64 = 1.0;
+
+/// Rule of initial population with its check result
+#[derive(Debug)]
+pub struct PopulationRule {
+    pub description: &'static str,
+    /// Number of checked rows (districts, customers, orders or order lines)
+    pub checked: i64,
+    /// Number of rows violating the rule
+    pub violations: i64,
+    /// Violating rows, up to MAX_SAMPLES
+    pub samples: Vec<String>,
+}
+
+impl PopulationRule {
+    fn new(description: &'static str) -> Self {
+        Self {
+            description,
+            checked: 0,
+            violations: 0,
+            samples: vec![],
+        }
+    }
+
+    /// Record check result of single row
+    fn check<F: FnOnce() -> String>(&mut self, valid: bool, sample: F) {
+        self.checked += 1;
+        if !valid {
+            self.violations += 1;
+            if self.samples.len() < MAX_SAMPLES {
+                self.samples.push(sample());
+            }
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.violations == 0
+    }
+}
+
+/// Share of rows having random property
+#[derive(Debug)]
+pub struct PopulationShare {
+    pub description: &'static str,
+    /// Number of all rows
+    pub rows: i64,
+    /// Number of rows having the property
+    pub matched: i64,
+    /// Expected share in percent
+    pub expected: f64,
+}
+
+impl PopulationShare {
+    fn new(description: &'static str, rows: i64, matched: i64, expected: f64) -> Self {
+        Self {
+            description,
+            rows,
+            matched,
+            expected,
+        }
+    }
+
+    /// Share in percent
+    pub fn share(&self) -> f64 {
+        self.matched as f64 * 100.0 / self.rows.max(1) as f64
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (self.share() - self.expected).abs() <= SHARE_TOLERANCE
+    }
+}
+
+/// Check rules and shares of initial population
+pub fn check(conn: &mut RdConnection) -> QueryResult<(Vec<PopulationRule>, Vec<PopulationShare>)> {
+    use schema::{customers, items, stocks};
+
+    let mut last_names =
+        PopulationRule::new("C_LAST of C_ID 1..=1000 is generated sequentially in each district");
+    let mut permutation = PopulationRule::new("O_C_ID is a permutation of C_ID in each district");
+    let mut new_orders = PopulationRule::new(
+        "The last 900 orders (O_ID 2101..=3000) are undelivered and in NEW-ORDER in each district",
+    );
+    let mut order_lines = PopulationRule::new(
+        "OL_NUMBER is 1..=O_OL_CNT, OL_AMOUNT is 0.00 if delivered, 0.01..=9999.99 if not",
+    );
+
+    let districts = schema::districts::table
+        .select((schema::districts::d_w_id, schema::districts::d_id))
+        .order((schema::districts::d_w_id, schema::districts::d_id))
+        .load::<(i32, i32)>(conn.as_db())?;
+
+    check_last_names(&mut last_names, conn)?;
+    check_districts(&districts, &mut permutation, &mut new_orders, conn)?;
+    for &(w_id, d_id) in &districts {
+        check_order_lines(w_id, d_id, &mut order_lines, conn)?;
+    }
+
+    let customer_count = customers::table
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+    let bad_credit = customers::table
+        .filter(customers::c_credit.eq("BC"))
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+    let item_count = items::table
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+    let original_items = items::table
+        .filter(items::i_data.like("%ORIGINAL%"))
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+    let stock_count = stocks::table
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+    let original_stocks = stocks::table
+        .filter(stocks::s_data.like("%ORIGINAL%"))
+        .select(count_star())
+        .first::<i64>(conn.as_db())?;
+
+    let rules = vec![last_names, permutation, new_orders, order_lines];
+    let shares = vec![
+        PopulationShare::new("C_CREDIT = 'BC'", customer_count, bad_credit, 10.0),
+        PopulationShare::new(
+            "I_DATA contains 'ORIGINAL'",
+            item_count,
+            original_items,
+            10.0,
+        ),
+        PopulationShare::new(
+            "S_DATA contains 'ORIGINAL'",
+            stock_count,
+            original_stocks,
+            10.0,
+        ),
+    ];
+    Ok((rules, shares))
+}
+
+/// TPC-C standard spec. 4.3.2.3, C_LAST of the first 1000 customers in a district
+/// are generated by numbers 0..=999 in sequence
+fn check_last_names(rule: &mut PopulationRule, conn: &mut RdConnection) -> QueryResult<()> {
+    use schema::customers;
+
+    let customers = customers::table
+        .filter(customers::c_id.le(1000))
+        .select((
+            customers::c_w_id,
+            customers::c_d_id,
+            customers::c_id,
+            customers::c_last,
+        ))
+        .order((customers::c_w_id, customers::c_d_id, customers::c_id))
+        .load::<(i32, i32, i32, String)>(conn.as_db())?;
+    for (w_id, d_id, c_id, c_last) in customers {
+        let expected = tpcc_rand::TpcRandom::last_name(c_id - 1);
+        rule.check(c_last == expected, || {
+            format!(
+                "W_ID={}, D_ID={}, C_ID={}: C_LAST={}, expected {}",
+                w_id, d_id, c_id, c_last, expected
+            )
+        });
+    }
+    Ok(())
+}
+
+/// Customers of orders and new orders in each district
+fn check_districts(
+    districts: &[(i32, i32)],
+    permutation: &mut PopulationRule,
+    new_order_rule: &mut PopulationRule,
+    conn: &mut RdConnection,
+) -> QueryResult<()> {
+    use schema::{customers, new_orders, orders};
+    use std::collections::HashMap;
+
+    let customer_counts = customers::table
+        .group_by((customers::c_w_id, customers::c_d_id))
+        .select((customers::c_w_id, customers::c_d_id, count_star()))
+        .load::<(i32, i32, i64)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, count)| ((w_id, d_id), count))
+        .collect::<HashMap<_, _>>();
+    // (count, min(O_C_ID), max(O_C_ID)) for each district
+    let order_customers = orders::table
+        .group_by((orders::o_w_id, orders::o_d_id))
+        .select((
+            orders::o_w_id,
+            orders::o_d_id,
+            count_star(),
+            diesel::dsl::min(orders::o_c_id),
+            diesel::dsl::max(orders::o_c_id),
+        ))
+        .load::<(i32, i32, i64, Option<i32>, Option<i32>)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, count, min_id, max_id)| ((w_id, d_id), (count, min_id, max_id)))
+        .collect::<HashMap<_, _>>();
+    // O_C_ID in more than one order, counted for each district
+    let mut duplicated_customers = HashMap::<(i32, i32), usize>::new();
+    for w_d_id in orders::table
+        .group_by((orders::o_w_id, orders::o_d_id, orders::o_c_id))
+        .having(count_star().gt(1))
+        .select((orders::o_w_id, orders::o_d_id))
+        .load::<(i32, i32)>(conn.as_db())?
+    {
+        *duplicated_customers.entry(w_d_id).or_default() += 1;
+    }
+    // (count, min(O_ID)) of undelivered orders for each district
+    let undelivered = orders::table
+        .filter(orders::o_carrier_id.is_null())
+        .group_by((orders::o_w_id, orders::o_d_id))
+        .select((
+            orders::o_w_id,
+            orders::o_d_id,
+            count_star(),
+            diesel::dsl::min(orders::o_id),
+        ))
+        .load::<(i32, i32, i64, Option<i32>)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, count, min_id)| ((w_id, d_id), (count, min_id)))
+        .collect::<HashMap<_, _>>();
+    // (count, min(NO_O_ID), max(NO_O_ID)) for each district
+    let new_orders = new_orders::table
+        .group_by((new_orders::no_w_id, new_orders::no_d_id))
+        .select((
+            new_orders::no_w_id,
+            new_orders::no_d_id,
+            count_star(),
+            diesel::dsl::min(new_orders::no_o_id),
+            diesel::dsl::max(new_orders::no_o_id),
+        ))
+        .load::<(i32, i32, i64, Option<i32>, Option<i32>)>(conn.as_db())?
+        .into_iter()
+        .map(|(w_id, d_id, count, min_id, max_id)| ((w_id, d_id), (count, min_id, max_id)))
+        .collect::<HashMap<_, _>>();
+
+    for &(w_id, d_id) in districts {
+        let customers = customer_counts.get(&(w_id, d_id)).copied().unwrap_or(0);
+        let (orders, min_c_id, max_c_id) = order_customers
+            .get(&(w_id, d_id))
+            .copied()
+            .unwrap_or((0, None, None));
+        let duplicated = duplicated_customers
+            .get(&(w_id, d_id))
+            .copied()
+            .unwrap_or(0);
+        permutation.check(
+            orders == customers
+                && duplicated == 0
+                && min_c_id == Some(1)
+                && max_c_id == Some(customers as i32),
+            || {
+                format!(
+                    "W_ID={}, D_ID={}: {} customers, {} orders in C_ID {:?}..={:?}, \
+                     {} customers in more than one order",
+                    w_id, d_id, customers, orders, min_c_id, max_c_id, duplicated
+                )
+            },
+        );
+
+        let last_o_id = DELIVERED_ORDERS + NEW_ORDERS_PER_DISTRICT as i32;
+        let (undelivered_count, min_undelivered) =
+            undelivered.get(&(w_id, d_id)).copied().unwrap_or((0, None));
+        let (new_order_count, min_no_o_id, max_no_o_id) = new_orders
+            .get(&(w_id, d_id))
+            .copied()
+            .unwrap_or((0, None, None));
+        new_order_rule.check(
+            new_order_count == NEW_ORDERS_PER_DISTRICT
+                && min_no_o_id == Some(DELIVERED_ORDERS + 1)
+                && max_no_o_id == Some(last_o_id)
+                && undelivered_count == NEW_ORDERS_PER_DISTRICT
+                && min_undelivered == Some(DELIVERED_ORDERS + 1),
+            || {
+                format!(
+                    "W_ID={}, D_ID={}: {} NEW-ORDER in NO_O_ID {:?}..={:?}, {} undelivered orders from O_ID {:?}",
+                    w_id, d_id, new_order_count, min_no_o_id, max_no_o_id, undelivered_count, min_undelivered
+                )
+            },
+        );
+    }
+    Ok(())
+}
+
+/// OL_NUMBER and OL_AMOUNT of each order in single district
+fn check_order_lines(
+    w_id: i32,
+    d_id: i32,
+    rule: &mut PopulationRule,
+    conn: &mut RdConnection,
+) -> QueryResult<()> {
+    use schema::{order_lines, orders};
+    use std::collections::HashMap;
+
+    // (lines, min(OL_NUMBER), max(OL_NUMBER)) for each order
+    let line_numbers = order_lines::table
+        .filter(order_lines::ol_w_id.eq(w_id))
+        .filter(order_lines::ol_d_id.eq(d_id))
+        .group_by(order_lines::ol_o_id)
+        .select((
+            order_lines::ol_o_id,
+            count_star(),
+            diesel::dsl::min(order_lines::ol_number),
+            diesel::dsl::max(order_lines::ol_number),
+        ))
+        .load::<(i32, i64, Option<i32>, Option<i32>)>(conn.as_db())?
+        .into_iter()
+        .map(|(o_id, lines, min_number, max_number)| (o_id, (lines, min_number, max_number)))
+        .collect::<HashMap<_, _>>();
+    // Order lines of delivered orders with non-zero amount,
+    // or order lines of undelivered orders with amount out of range
+    let bad_amounts = order_lines::table
+        .filter(order_lines::ol_w_id.eq(w_id))
+        .filter(order_lines::ol_d_id.eq(d_id))
+        .filter(
+            order_lines::ol_o_id
+                .le(DELIVERED_ORDERS)
+                .and(order_lines::ol_amount.ne(0.0))
+                .or(order_lines::ol_o_id.gt(DELIVERED_ORDERS).and(
+                    order_lines::ol_amount
+                        .lt(0.01)
+                        .or(order_lines::ol_amount.gt(9_999.99)),
+                )),
+        )
+        .group_by(order_lines::ol_o_id)
+        .select((order_lines::ol_o_id, count_star()))
+        .load::<(i32, i64)>(conn.as_db())?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let orders = orders::table
+        .filter(orders::o_w_id.eq(w_id))
+        .filter(orders::o_d_id.eq(d_id))
+        .select((orders::o_id, orders::o_ol_cnt))
+        .order(orders::o_id)
+        .load::<(i32, i32)>(conn.as_db())?;
+    for (o_id, o_ol_cnt) in orders {
+        let (lines, min_number, max_number) =
+            line_numbers.get(&o_id).copied().unwrap_or((0, None, None));
+        let bad_amount = bad_amounts.get(&o_id).copied().unwrap_or(0);
+        rule.check(
+            lines == o_ol_cnt as i64
+                && min_number == Some(1)
+                && max_number == Some(o_ol_cnt)
+                && bad_amount == 0,
+            || {
+                format!(
+                    "W_ID={}, D_ID={}, O_ID={}: O_OL_CNT={}, {} lines in OL_NUMBER {:?}..={:?}, {} lines with invalid OL_AMOUNT",
+                    w_id, d_id, o_id, o_ol_cnt, lines, min_number, max_number, bad_amount
+                )
+            },
+        );
+    }
+    Ok(())
+}
@@ -74,6 +74,13 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    nurand_constants (nc_a) {
+        nc_a -> Integer,
+        nc_c -> Integer,
+    }
+}
+
 diesel::table! {
     order_lines (ol_o_id, ol_d_id, ol_w_id, ol_number) {
         ol_o_id -> Integer,
@@ -148,6 +155,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     histories,
     items,
     new_orders,
+    nurand_constants,
     order_lines,
     orders,
     stocks,
@@ -1,66 +1,154 @@
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
-pub type DbConnection = diesel::sqlite::SqliteConnection;
-pub type Pool = diesel::r2d2::Pool<diesel::r2d2::ConnectionManager<DbConnection>>;
+use diesel::sqlite::SqliteConnection;
 
-/// Connect to database
-pub fn connect(db_url: &str) -> ConnectionResult<DbConnection> {
-    use diesel::prelude::Connection;
+/// Connect to database, `db_url` is a file path without `sqlite:` scheme
+pub(crate) fn establish(db_url: &str) -> ConnectionResult<SqliteConnection> {
+    SqliteConnection::establish(db_url)
+}
+
+/// SQLite setup in initial connection
+pub(crate) fn setup_conn(
+    conn: &mut SqliteConnection,
+    options: &crate::SqliteOptions,
+) -> QueryResult<()> {
+    // locking_mode must precede journal_mode, WAL in EXCLUSIVE mode does not use shared memory
+    if let Some(locking_mode) = options.locking_mode {
+        conn.batch_execute(&format!("PRAGMA locking_mode = {};", locking_mode.as_sql()))?;
+    }
+    // SQLite keeps current mode if the mode is not supported, e.g. WAL2 out of wal2 branch
+    let journal_mode = diesel::sql_query(format!(
+        "PRAGMA journal_mode = {};",
+        options.journal_mode.as_sql()
+    ))
+    .get_result::<PragmaJournalMode>(conn)?;
+    if !journal_mode
+        .journal_mode
+        .eq_ignore_ascii_case(options.journal_mode.as_sql())
+    {
+        return Err(diesel::result::Error::QueryBuilderError(
+            format!(
+                "journal_mode {} is not available, current mode is {}",
+                options.journal_mode.as_sql(),
+                journal_mode.journal_mode
+            )
+            .into(),
+        ));
+    }
+    if let Some(synchronous) = options.synchronous {
+        conn.batch_execute(&format!("PRAGMA synchronous = {};", synchronous.as_sql()))?;
+    }
+    conn.batch_execute(&format!("PRAGMA cache_size = {};", options.cache_size))?;
+    if let Some(mmap_size) = options.mmap_size {
+        conn.batch_execute(&format!("PRAGMA mmap_size = {};", mmap_size))?;
+    }
+    if let Some(temp_store) = options.temp_store {
+        conn.batch_execute(&format!("PRAGMA temp_store = {};", temp_store.as_sql()))?;
+    }
+    // Force foreign key constraint
+    conn.batch_execute("PRAGMA foreign_keys = ON;")?;
+    // Timeout
+    conn.batch_execute(&format!("PRAGMA busy_timeout = {};", options.busy_timeout))?; // mili-sec
+    Ok(())
+}
 
-    let mut conn = DbConnection::establish(db_url)?;
-    setup_conn(&mut conn).map_err(|e| ConnectionError::CouldntSetupConfiguration(e))?;
-    Ok(conn)
+#[derive(QueryableByName)]
+struct PragmaJournalMode {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    journal_mode: String,
 }
 
-/// Make database pool
-pub fn pool(db_url: &str, connections: u32) -> Result<Pool, diesel::r2d2::PoolError> {
-    let manager = diesel::r2d2::ConnectionManager::<DbConnection>::new(db_url);
+/// PRAGMAs in effect
+pub(crate) fn database_settings(conn: &mut SqliteConnection) -> QueryResult<Vec<(String, String)>> {
+    let pragmas = diesel::sql_query(
+        "SELECT 'journal_mode' AS name, journal_mode AS value FROM pragma_journal_mode \
+         UNION ALL SELECT 'synchronous', CASE synchronous \
+           WHEN 0 THEN 'OFF' WHEN 1 THEN 'NORMAL' WHEN 2 THEN 'FULL' ELSE 'EXTRA' END \
+           FROM pragma_synchronous \
+         UNION ALL SELECT 'cache_size', CAST(cache_size AS TEXT) FROM pragma_cache_size \
+         UNION ALL SELECT 'temp_store', CASE temp_store \
+           WHEN 0 THEN 'DEFAULT' WHEN 1 THEN 'FILE' ELSE 'MEMORY' END FROM pragma_temp_store \
+         UNION ALL SELECT 'locking_mode', locking_mode FROM pragma_locking_mode \
+         UNION ALL SELECT 'busy_timeout', CAST(timeout AS TEXT) FROM pragma_busy_timeout \
+         UNION ALL SELECT 'foreign_keys', CAST(foreign_keys AS TEXT) FROM pragma_foreign_keys",
+    )
+    .load::<Pragma>(conn)?;
+    // mmap_size has no table-valued function
+    let mmap_size = diesel::sql_query("PRAGMA mmap_size").get_result::<PragmaMmapSize>(conn)?;
 
-    Pool::builder()
-        .max_size(connections)
-        .connection_customizer(Box::new(CustomOptions()))
-        .build(manager)
+    let mut settings = pragmas
+        .into_iter()
+        .map(|p| (p.name, p.value.to_uppercase()))
+        .collect::<Vec<_>>();
+    settings.push(("mmap_size".to_string(), mmap_size.mmap_size.to_string()));
+    Ok(settings)
 }
 
-/// Customize Sqlite options
-#[derive(Debug)]
-struct CustomOptions();
-impl diesel::r2d2::CustomizeConnection<DbConnection, diesel::r2d2::Error> for CustomOptions {
-    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), diesel::r2d2::Error> {
-        setup_conn(conn).map_err(diesel::r2d2::Error::QueryError)
-    }
+#[derive(QueryableByName)]
+struct Pragma {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    value: String,
 }
 
-/// SQLite setup in initial connection
-fn setup_conn(conn: &mut DbConnection) -> QueryResult<()> {
-    use diesel::connection::SimpleConnection;
-    // SQLite on NFS can not use WAL, set journal_mode as default DELETE mode
-    conn.batch_execute("PRAGMA journal_mode = DELETE;")?;
-    // Enlarge buffer size to 32MiB
-    conn.batch_execute("PRAGMA cache_size = -32768;")?;
-    // Force foreign key constraint
-    conn.batch_execute("PRAGMA foreign_keys = ON;")?;
-    // Timeout
-    conn.batch_execute("PRAGMA busy_timeout = 3000;")?; // mili-sec
-    Ok(())
+#[derive(QueryableByName)]
+struct PragmaMmapSize {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    mmap_size: i64,
 }
 
-/// SQLite setup in initial connection
-pub fn vacuum(conn: &mut DbConnection) -> QueryResult<()> {
-    use diesel::connection::SimpleConnection;
+/// Run vacuum
+pub(crate) fn vacuum(conn: &mut SqliteConnection) -> QueryResult<()> {
     conn.batch_execute("VACUUM;")?;
     Ok(())
 }
 
+/// Drop secondary indexes before bulk load, B-tree is built faster from sorted rows
+pub(crate) fn drop_secondary_indexes(conn: &mut SqliteConnection) -> QueryResult<()> {
+    conn.batch_execute("DROP INDEX IF EXISTS idx_customer_lastname;")?;
+    Ok(())
+}
+
+/// Create secondary indexes after bulk load, same as migrations_sqlite
+pub(crate) fn create_secondary_indexes(conn: &mut SqliteConnection) -> QueryResult<()> {
+    conn.batch_execute(
+        "CREATE INDEX IF NOT EXISTS idx_customer_lastname ON customers (c_w_id, c_d_id, c_last);",
+    )?;
+    Ok(())
+}
+
 /// SQLite database size
-pub fn database_size(conn: &mut crate::RdConnection) -> QueryResult<i64> {
-    let page_count =
-        diesel::sql_query("PRAGMA page_count").get_result::<PragmaPageCount>(conn.as_db())?;
-    let page_size =
-        diesel::sql_query("PRAGMA page_size").get_result::<PragmaPageSize>(conn.as_db())?;
+pub(crate) fn database_size(conn: &mut SqliteConnection) -> QueryResult<i64> {
+    let page_count = diesel::sql_query("PRAGMA page_count").get_result::<PragmaPageCount>(conn)?;
+    let page_size = diesel::sql_query("PRAGMA page_size").get_result::<PragmaPageSize>(conn)?;
 
     Ok(page_count.page_count * page_size.page_size)
 }
 
+/// Rows and sizes of table, pages of the table and its indexes in dbstat virtual table
+pub(crate) fn table_size(
+    conn: &mut SqliteConnection,
+    table: &str,
+) -> QueryResult<crate::TableSize> {
+    diesel::sql_query(format!(
+        "SELECT '{table}' AS table_name, \
+         (SELECT COUNT(*) FROM {table}) AS table_rows, \
+         (SELECT COALESCE(SUM(pgsize), 0) FROM dbstat WHERE name IN \
+           (SELECT name FROM sqlite_master WHERE tbl_name = '{table}')) AS table_bytes, \
+         (SELECT COALESCE(SUM(pgsize), 0) FROM dbstat WHERE name IN \
+           (SELECT name FROM sqlite_master WHERE tbl_name = '{table}' AND type = 'index')) AS index_bytes",
+        table = table
+    ))
+    .get_result(conn)
+}
+
+/// Isolation level and READ ONLY of current transaction
+/// SQLite transaction is always SERIALIZABLE, except for read_uncommitted in shared cache mode.
+pub(crate) fn transaction_mode(_conn: &mut SqliteConnection) -> QueryResult<(String, bool)> {
+    Ok(("serializable".to_string(), false))
+}
+
 #[derive(QueryableByName)]
 struct PragmaPageCount {
     page_count: i64,
@@ -83,26 +171,11 @@ diesel::table! {
     }
 }
 
-impl crate::RwTransaction for DbConnection {
-    /// BEGIN DEFERRED transaction for read only queries.
-    /// SHARED lock will be held at first query statement (SELECT).
-    /// Write statements (INSERT, UPDATE, DELETE) will automatically trigger EXCLUSIVE lock.
-    fn read_transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
-    where
-        for<'b> F: FnOnce(&'b mut crate::RdConnection<'b>) -> Result<T, E>,
-        E: From<diesel::result::Error>,
-    {
-        diesel::connection::Connection::transaction(self, |conn| {
-            f(&mut crate::RdConnection::new(conn))
-        })
-    }
-
-    /// Try taking EXCLUSIVE lock for write
-    fn write_transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
-    where
-        for<'b> F: FnOnce(&'b mut crate::WrConnection<'b>) -> Result<T, E>,
-        E: From<diesel::result::Error>,
-    {
-        DbConnection::immediate_transaction(self, |conn| f(&mut crate::WrConnection::new(conn)))
-    }
+/// BEGIN IMMEDIATE, try taking RESERVED lock for write at first
+///
+/// BEGIN DEFERRED (default) for read only queries takes SHARED lock at first SELECT,
+/// and write statements (INSERT, UPDATE, DELETE) will trigger lock upgrade later.
+pub(crate) fn begin_write_transaction(conn: &mut SqliteConnection) -> QueryResult<()> {
+    use diesel::connection::AnsiTransactionManager;
+    AnsiTransactionManager::begin_transaction_sql(conn, "BEGIN IMMEDIATE")
 }
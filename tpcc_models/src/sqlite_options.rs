@@ -0,0 +1,181 @@
+/// SQLite journal mode
+/// https://www.sqlite.org/pragma.html#pragma_journal_mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JournalMode {
+    /// Delete rollback journal at the end of each transaction, works on NFS
+    #[default]
+    Delete,
+    /// Truncate rollback journal instead of deleting
+    Truncate,
+    /// Overwrite header of rollback journal instead of deleting
+    Persist,
+    /// Rollback journal in memory
+    Memory,
+    /// Write-ahead log, needs shared memory on local file system
+    Wal,
+    /// Write-ahead log with two log files, only in wal2 branch of SQLite
+    Wal2,
+    /// No rollback journal
+    Off,
+}
+
+impl JournalMode {
+    /// Mode in PRAGMA statement
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Delete => "DELETE",
+            Self::Truncate => "TRUNCATE",
+            Self::Persist => "PERSIST",
+            Self::Memory => "MEMORY",
+            Self::Wal => "WAL",
+            Self::Wal2 => "WAL2",
+            Self::Off => "OFF",
+        }
+    }
+}
+
+impl std::str::FromStr for JournalMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "delete" => Ok(Self::Delete),
+            "truncate" => Ok(Self::Truncate),
+            "persist" => Ok(Self::Persist),
+            "memory" => Ok(Self::Memory),
+            "wal" => Ok(Self::Wal),
+            "wal2" => Ok(Self::Wal2),
+            "off" => Ok(Self::Off),
+            _ => Err(format!("unknown journal mode {}", s)),
+        }
+    }
+}
+
+/// SQLite synchronous, fsync timing
+/// https://www.sqlite.org/pragma.html#pragma_synchronous
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Synchronous {
+    /// Setting in PRAGMA statement
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+            Self::Extra => "EXTRA",
+        }
+    }
+}
+
+impl std::str::FromStr for Synchronous {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "0" => Ok(Self::Off),
+            "normal" | "1" => Ok(Self::Normal),
+            "full" | "2" => Ok(Self::Full),
+            "extra" | "3" => Ok(Self::Extra),
+            _ => Err(format!("unknown synchronous {}", s)),
+        }
+    }
+}
+
+/// SQLite temp_store, location of temporary tables and indices
+/// https://www.sqlite.org/pragma.html#pragma_temp_store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TempStore {
+    Default,
+    File,
+    Memory,
+}
+
+impl TempStore {
+    /// Setting in PRAGMA statement
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Default => "DEFAULT",
+            Self::File => "FILE",
+            Self::Memory => "MEMORY",
+        }
+    }
+}
+
+impl std::str::FromStr for TempStore {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "default" | "0" => Ok(Self::Default),
+            "file" | "1" => Ok(Self::File),
+            "memory" | "2" => Ok(Self::Memory),
+            _ => Err(format!("unknown temp_store {}", s)),
+        }
+    }
+}
+
+/// SQLite locking_mode
+/// https://www.sqlite.org/pragma.html#pragma_locking_mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockingMode {
+    Normal,
+    /// Never release file lock, only one connection can access the database
+    Exclusive,
+}
+
+impl LockingMode {
+    /// Mode in PRAGMA statement
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Exclusive => "EXCLUSIVE",
+        }
+    }
+}
+
+impl std::str::FromStr for LockingMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "normal" => Ok(Self::Normal),
+            "exclusive" => Ok(Self::Exclusive),
+            _ => Err(format!("unknown locking mode {}", s)),
+        }
+    }
+}
+
+/// PRAGMAs applied to every SQLite connection
+///
+/// None leaves the SQLite default.
+#[derive(Debug, Clone)]
+pub struct SqliteOptions {
+    pub journal_mode: JournalMode,
+    pub synchronous: Option<Synchronous>,
+    /// Pages if positive, KiB if negative
+    pub cache_size: i64,
+    /// Bytes of memory mapped I/O
+    pub mmap_size: Option<i64>,
+    pub temp_store: Option<TempStore>,
+    pub locking_mode: Option<LockingMode>,
+    /// Wait for lock in milli-secs
+    pub busy_timeout: u32,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        Self {
+            // SQLite on NFS can not use WAL, set journal_mode as default DELETE mode
+            journal_mode: JournalMode::Delete,
+            synchronous: None,
+            // Enlarge buffer size to 32MiB
+            cache_size: -32768,
+            mmap_size: None,
+            temp_store: None,
+            locking_mode: None,
+            busy_timeout: 3000,
+        }
+    }
+}
@@ -15,6 +15,51 @@ pub trait RwTransaction {
         E: From<diesel::result::Error>;
 }
 
+/// Transaction isolation level
+/// TPC-C standard spec. 3.4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+    /// Prevents P0, P1 (PostgreSQL default)
+    #[default]
+    ReadCommitted,
+    /// Prevents P0, P1, P2
+    RepeatableRead,
+    /// Prevents P0, P1, P2, P3
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// Level in SQL statement
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::ReadCommitted => "READ COMMITTED",
+            Self::RepeatableRead => "REPEATABLE READ",
+            Self::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl std::str::FromStr for IsolationLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "read_committed" => Ok(Self::ReadCommitted),
+            "repeatable_read" => Ok(Self::RepeatableRead),
+            "serializable" => Ok(Self::Serializable),
+            _ => Err(format!("unknown isolation level {}", s)),
+        }
+    }
+}
+
+/// Options applied to every transaction in pooled connections
+#[derive(Debug, Clone, Default)]
+pub struct TransactionOptions {
+    /// Isolation level of both read and write transactions
+    pub isolation_level: IsolationLevel,
+    /// Run read_transaction() as READ ONLY
+    pub read_only: bool,
+}
+
 /// Database connection used only for read operation
 pub struct RdConnection<'a>(&'a mut crate::DbConnection);
 impl<'a> RdConnection<'a> {
@@ -1,11 +1,101 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+
+/// Constant C of NURand(A, x, y) for each field
+/// TPC-C standard spec. 2.1.6
+/// C is a run-time constant randomly chosen within [0 .. A],
+///   the same C value per field must be used by all emulated terminals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NURandConstants {
+    /// C for C_LAST, A = 255
+    pub c_last: i32,
+    /// C for C_ID, A = 1023
+    pub c_id: i32,
+    /// C for OL_I_ID, A = 8191
+    pub ol_i_id: i32,
+}
+
+impl NURandConstants {
+    /// Randomly chosen constants, C_LOAD for initial population
+    pub fn random(rand: &mut TpcRandom) -> Self {
+        Self {
+            c_last: rand.i32_range(0..=255),
+            c_id: rand.i32_range(0..=1023),
+            ol_i_id: rand.i32_range(0..=8191),
+        }
+    }
+
+    /// TPC-C standard spec. 2.1.6.1
+    /// C_RUN for measurement derived from C_LOAD (self) used in initial population
+    /// C_DELTA = |C_RUN - C_LOAD| of C_LAST must satisfy 65 <= C_DELTA <= 119,
+    ///   excluding 96 and 112. C_ID and OL_I_ID are not used in population.
+    pub fn for_run(&self, rand: &mut TpcRandom) -> Self {
+        let c_last = loop {
+            let c_run = rand.i32_range(0..=255);
+            if Self::is_valid_delta(c_run, self.c_last) {
+                break c_run;
+            }
+        };
+        Self {
+            c_last,
+            c_id: rand.i32_range(0..=1023),
+            ol_i_id: rand.i32_range(0..=8191),
+        }
+    }
+
+    fn is_valid_delta(c_run: i32, c_load: i32) -> bool {
+        let delta = (c_run - c_load).abs();
+        (65..=119).contains(&delta) && delta != 96 && delta != 112
+    }
+}
 
-pub struct TpcRandom(rand::rngs::ThreadRng);
+/// ChaCha8 generates the same sequence from the same seed on every platform and version
+pub struct TpcRandom {
+    rng: rand_chacha::ChaCha8Rng,
+    nurand: NURandConstants,
+}
 
 impl TpcRandom {
-    /// New random number generator
+    /// New random number generator seeded from OS entropy
     pub fn new() -> Self {
-        Self(rand::thread_rng())
+        Self::with_rng(rand_chacha::ChaCha8Rng::from_entropy())
+    }
+
+    /// Reproducible random number generator
+    pub fn from_seed(seed: u64) -> Self {
+        Self::with_rng(rand_chacha::ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Reproducible random number generator of independent `stream` under the same `seed`,
+    /// e.g. for each terminal or loader task
+    pub fn from_seed_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(stream);
+        Self::with_rng(rng)
+    }
+
+    /// NURand constants are chosen randomly, replace them by `with_nurand` to share them
+    fn with_rng(rng: rand_chacha::ChaCha8Rng) -> Self {
+        let mut rand = Self {
+            rng,
+            nurand: NURandConstants {
+                c_last: 0,
+                c_id: 0,
+                ol_i_id: 0,
+            },
+        };
+        rand.nurand = NURandConstants::random(&mut rand);
+        rand
+    }
+
+    /// Use `nurand` constants in NURand of all fields
+    pub fn with_nurand(mut self, nurand: NURandConstants) -> Self {
+        self.nurand = nurand;
+        self
+    }
+
+    /// NURand constants in use
+    pub fn nurand(&self) -> NURandConstants {
+        self.nurand
     }
 
     fn alnum_bytes(&mut self, len: usize) -> Vec<u8> {
@@ -20,7 +110,7 @@ impl TpcRandom {
         // Select CHARS in random
         let bytes = (0..len)
             .map(|_| {
-                let r = self.0.gen_range(0..CHARS.len());
+                let r = self.rng.gen_range(0..CHARS.len());
                 CHARS[r]
             })
             .collect::<Vec<u8>>();
@@ -32,7 +122,7 @@ impl TpcRandom {
     ///   26 upper case letters, and the digits 0 to 9
     pub fn alnum_string(&mut self, len: std::ops::RangeInclusive<usize>) -> String {
         // Random length
-        let len = self.0.gen_range((*len.start())..=(*len.end()));
+        let len = self.rng.gen_range((*len.start())..=(*len.end()));
         // Random chars
         let bytes = self.alnum_bytes(len);
 
@@ -47,7 +137,7 @@ impl TpcRandom {
         // Select CHARS in random
         let bytes = (0..len)
             .map(|_| {
-                let r = self.0.gen_range(0..CHARS.len());
+                let r = self.rng.gen_range(0..CHARS.len());
                 CHARS[r]
             })
             .collect::<Vec<u8>>();
@@ -58,19 +148,50 @@ impl TpcRandom {
 
     /// Uniformly distributed i32 value in range
     pub fn i32_range(&mut self, range: std::ops::RangeInclusive<i32>) -> i32 {
-        self.0.gen_range(range)
+        self.rng.gen_range(range)
     }
 
-    pub fn non_uniform_i32(&mut self, mask: i32, range: std::ops::RangeInclusive<i32>) -> i32 {
-        const RUNTIME_CONST_C: i32 = 100;
-        let r = (self.0.gen_range(0..=mask) | self.0.gen_range(range.clone())) + RUNTIME_CONST_C;
+    /// TPC-C standard spec. 2.1.6
+    /// NURand(A, x, y) = (((random(0, A) | random(x, y)) + C) % (y - x + 1)) + x
+    fn non_uniform_i32(&mut self, a: i32, c: i32, range: std::ops::RangeInclusive<i32>) -> i32 {
+        let r = (self.rng.gen_range(0..=a) | self.rng.gen_range(range.clone())) + c;
         let w = range.end() - range.start() + 1;
         r % w + range.start()
     }
 
+    /// Index of customer last name, NURand(255, 0, 999), TPC-C standard spec. 4.3.2.3
+    pub fn last_name_index(&mut self) -> i32 {
+        self.non_uniform_i32(255, self.nurand.c_last, 0..=999)
+    }
+
+    /// Customer id, NURand(1023, 1, 3000), TPC-C standard spec. 2.4.1.2
+    pub fn customer_id(&mut self) -> i32 {
+        self.non_uniform_i32(1023, self.nurand.c_id, 1..=3000)
+    }
+
+    /// Item id, NURand(8191, 1, 100000), TPC-C standard spec. 2.4.1.5
+    pub fn item_id(&mut self) -> i32 {
+        self.non_uniform_i32(8191, self.nurand.ol_i_id, 1..=100_000)
+    }
+
     /// Uniformly distributed f64 value in range
     pub fn f64_range(&mut self, range: std::ops::RangeInclusive<f64>) -> f64 {
-        self.0.gen_range(range)
+        self.rng.gen_range(range)
+    }
+
+    /// Shuffle slice in random order
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        use rand::seq::SliceRandom;
+        slice.shuffle(&mut self.rng);
+    }
+
+    /// TPC-C standard spec. 5.2.5.4
+    /// Think time in negative exponential distribution,
+    ///   truncated at 10 times of mean value
+    pub fn think_time(&mut self, mean: f64) -> f64 {
+        // (0.0, 1.0] to avoid ln(0)
+        let r = 1.0 - self.rng.gen::<f64>();
+        f64::min(-r.ln() * mean, 10.0 * mean)
     }
 
     /// TPC-C standard spec. 4.3.2.7
@@ -78,17 +199,17 @@ impl TpcRandom {
     ///    1. A random n-string of 4 numbers, and
     ///    2. The constant string '11111'.
     pub fn zip_code(&mut self) -> String {
-        format!("{:04}11111", self.0.gen_range(0..=9999))
+        format!("{:04}11111", self.rng.gen_range(0..=9999))
     }
 
     /// items.i_data, 10% contains "ORIGINAL"
     pub fn item_data(&mut self) -> String {
         // Random length
-        let len = self.0.gen_range(26..=50);
+        let len = self.rng.gen_range(26..=50);
 
-        let bytes = if 0 == self.0.gen_range(0..=9) {
+        let bytes = if 0 == self.rng.gen_range(0..=9) {
             // Insert ORIGINAL
-            let pos = self.0.gen_range(0..=(len - 8));
+            let pos = self.rng.gen_range(0..=(len - 8));
             let mut bytes1 = self.alnum_bytes(pos as usize);
             let bytes2 = self.alnum_bytes((len - pos - 8) as usize);
 